@@ -0,0 +1,98 @@
+//
+// Created on Mon Jun 10 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+/// in-memory stand-in for the plasma contract
+pub mod memory;
+
+use crate::error::Error;
+use ethereum_types::H256;
+use plasma_core::data_structure::{InclusionProof, StateUpdate, Transaction};
+use std::sync::Arc;
+
+/// An exit which was started on the plasma contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exit {
+    id: u64,
+    state_update: StateUpdate,
+}
+
+impl Exit {
+    pub fn new(id: u64, state_update: &StateUpdate) -> Self {
+        Exit {
+            id,
+            state_update: state_update.clone(),
+        }
+    }
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+    pub fn get_state_update(&self) -> &StateUpdate {
+        &self.state_update
+    }
+}
+
+/// A challenge against an exit.
+/// `deprecation` is a verified state update which was created later than the exited one
+/// over the same range, and `transaction` is the transaction spending the exited state into it.
+/// Its inclusion proof proves the spend against the block root committed on the plasma contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Challenge {
+    exit_id: u64,
+    deprecation: StateUpdate,
+    transaction: Transaction,
+    inclusion_proof: InclusionProof,
+}
+
+impl Challenge {
+    pub fn new(
+        exit_id: u64,
+        deprecation: &StateUpdate,
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+    ) -> Self {
+        Challenge {
+            exit_id,
+            deprecation: deprecation.clone(),
+            transaction: transaction.clone(),
+            inclusion_proof: inclusion_proof.clone(),
+        }
+    }
+    pub fn get_exit_id(&self) -> u64 {
+        self.exit_id
+    }
+    pub fn get_deprecation(&self) -> &StateUpdate {
+        &self.deprecation
+    }
+    pub fn get_transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+    pub fn get_inclusion_proof(&self) -> &InclusionProof {
+        &self.inclusion_proof
+    }
+}
+
+/// Interface of the plasma contract on L1 which clients talk to.
+pub trait PlasmaContract {
+    /// get exits whose id is greater than or equal to `from_id`
+    fn get_exits(&self, from_id: u64) -> Result<Vec<Exit>, Error>;
+    /// submit a challenge against an exit
+    fn challenge_exit(&self, challenge: &Challenge) -> Result<(), Error>;
+    /// get the root committed by the operator for a block
+    fn get_block_root(&self, block_number: u64) -> Result<Option<H256>, Error>;
+}
+
+impl<C: PlasmaContract> PlasmaContract for Arc<C> {
+    fn get_exits(&self, from_id: u64) -> Result<Vec<Exit>, Error> {
+        (**self).get_exits(from_id)
+    }
+    fn challenge_exit(&self, challenge: &Challenge) -> Result<(), Error> {
+        (**self).challenge_exit(challenge)
+    }
+    fn get_block_root(&self, block_number: u64) -> Result<Option<H256>, Error> {
+        (**self).get_block_root(block_number)
+    }
+}
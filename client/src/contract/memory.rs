@@ -0,0 +1,52 @@
+use super::{Challenge, Exit, PlasmaContract};
+use crate::error::Error;
+use ethereum_types::H256;
+use parking_lot::RwLock;
+use plasma_core::data_structure::StateUpdate;
+use std::collections::HashMap;
+
+/// Plasma contract which lives in memory.
+/// It behaves as a local chain for development and tests.
+#[derive(Default)]
+pub struct MemoryPlasmaContract {
+    exits: RwLock<Vec<Exit>>,
+    challenges: RwLock<Vec<Challenge>>,
+    roots: RwLock<HashMap<u64, H256>>,
+}
+
+impl MemoryPlasmaContract {
+    /// start an exit and returns its id
+    pub fn start_exit(&self, state_update: &StateUpdate) -> u64 {
+        let mut exits = self.exits.write();
+        let id = exits.len() as u64;
+        exits.push(Exit::new(id, state_update));
+        id
+    }
+    /// commit a root of a block
+    pub fn commit_block(&self, block_number: u64, root: &H256) {
+        self.roots.write().insert(block_number, *root);
+    }
+    /// get challenges submitted so far
+    pub fn get_challenges(&self) -> Vec<Challenge> {
+        self.challenges.read().clone()
+    }
+}
+
+impl PlasmaContract for MemoryPlasmaContract {
+    fn get_exits(&self, from_id: u64) -> Result<Vec<Exit>, Error> {
+        Ok(self
+            .exits
+            .read()
+            .iter()
+            .filter(|exit| exit.get_id() >= from_id)
+            .cloned()
+            .collect())
+    }
+    fn challenge_exit(&self, challenge: &Challenge) -> Result<(), Error> {
+        self.challenges.write().push(challenge.clone());
+        Ok(())
+    }
+    fn get_block_root(&self, block_number: u64) -> Result<Option<H256>, Error> {
+        Ok(self.roots.read().get(&block_number).cloned())
+    }
+}
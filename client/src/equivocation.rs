@@ -0,0 +1,224 @@
+//
+// Created on Wed Jun 12 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use crate::contract::PlasmaContract;
+use crate::error::{Error, ErrorKind};
+use ethereum_types::{Address, H256};
+use parking_lot::RwLock;
+use plasma_core::data_structure::SignedRoot;
+use plasma_db::traits::kvs::KeyValueStore;
+
+/// Verifiable evidence that the operator signed two different roots for one block number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EquivocationEvidence {
+    first: SignedRoot,
+    second: SignedRoot,
+}
+
+impl EquivocationEvidence {
+    pub fn new(first: &SignedRoot, second: &SignedRoot) -> Self {
+        EquivocationEvidence {
+            first: first.clone(),
+            second: second.clone(),
+        }
+    }
+    pub fn get_first(&self) -> &SignedRoot {
+        &self.first
+    }
+    pub fn get_second(&self) -> &SignedRoot {
+        &self.second
+    }
+    /// Anyone can check the evidence only with the operator address.
+    pub fn verify(&self, operator: &Address) -> bool {
+        self.first.get_block_number() == self.second.get_block_number()
+            && self.first.get_root() != self.second.get_root()
+            && self.first.verify(operator)
+            && self.second.verify(operator)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Equivocation {
+    /// The operator signed two different roots for one block number.
+    ConflictingRoots(EquivocationEvidence),
+    /// The operator signed a root which is different from the root committed on L1.
+    UncommittedRoot {
+        signed_root: SignedRoot,
+        committed_root: H256,
+    },
+}
+
+/// EquivocationDetector records the root seen for each block number,
+/// and compares it with roots reported by peers and roots committed on L1.
+/// Seen roots should be kept in a persistent db, so that a conflicting root is detected after restart.
+pub struct EquivocationDetector {
+    operator: Address,
+    db: RwLock<Box<dyn KeyValueStore + Send + Sync>>,
+}
+
+impl EquivocationDetector {
+    pub fn new(operator: &Address, db: Box<dyn KeyValueStore + Send + Sync>) -> Self {
+        EquivocationDetector {
+            operator: *operator,
+            db: RwLock::new(db),
+        }
+    }
+    fn verify_signature(&self, signed_root: &SignedRoot) -> Result<(), Error> {
+        if signed_root.verify(&self.operator) {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::InvalidSignature))
+        }
+    }
+    fn compare(&self, signed_root: &SignedRoot) -> Result<Option<Equivocation>, Error> {
+        Ok(self
+            .get_root(signed_root.get_block_number())?
+            .filter(|seen| seen.get_root() != signed_root.get_root())
+            .map(|seen| {
+                Equivocation::ConflictingRoots(EquivocationEvidence::new(&seen, signed_root))
+            }))
+    }
+    /// get the signed root which this client saw for `block_number`
+    pub fn get_root(&self, block_number: u64) -> Result<Option<SignedRoot>, Error> {
+        let encoded = self.db.read().get(&block_number.to_be_bytes())?;
+        encoded
+            .map(|encoded| SignedRoot::from_abi(&encoded).map_err(Into::into))
+            .transpose()
+    }
+    /// Record a root received from the operator.
+    /// The first root for a block number is kept, and following different ones are reported.
+    pub fn record_root(&self, signed_root: &SignedRoot) -> Result<Option<Equivocation>, Error> {
+        self.verify_signature(signed_root)?;
+        let equivocation = self.compare(signed_root)?;
+        if self.get_root(signed_root.get_block_number())?.is_none() {
+            self.db.write().put(
+                &signed_root.get_block_number().to_be_bytes(),
+                &signed_root.to_abi(),
+            )?;
+        }
+        Ok(equivocation)
+    }
+    /// Compare a root reported by a peer with the recorded one.
+    pub fn check_peer_root(&self, signed_root: &SignedRoot) -> Result<Option<Equivocation>, Error> {
+        self.verify_signature(signed_root)?;
+        self.compare(signed_root)
+    }
+    /// Compare the recorded root with the root committed on L1.
+    pub fn check_commitment(
+        &self,
+        contract: &dyn PlasmaContract,
+        block_number: u64,
+    ) -> Result<Option<Equivocation>, Error> {
+        let seen = self.get_root(block_number)?;
+        let committed_root = contract.get_block_root(block_number)?;
+        Ok(match (seen, committed_root) {
+            (Some(signed_root), Some(committed_root))
+                if signed_root.get_root() != &committed_root =>
+            {
+                Some(Equivocation::UncommittedRoot {
+                    signed_root,
+                    committed_root,
+                })
+            }
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Equivocation, EquivocationDetector};
+    use crate::contract::memory::MemoryPlasmaContract;
+    use ethereum_types::H256;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::data_structure::{Signature, SignedRoot};
+    use plasma_db::impls::kvs::kvdb::CoreDb;
+    use plasma_db::impls::kvs::leveldb::KvsLevelImpl;
+    use plasma_db::traits::db::DatabaseTrait;
+    use tempdir::TempDir;
+
+    fn operator_key() -> SecretKey {
+        SecretKey::parse(&[1u8; 32]).unwrap()
+    }
+
+    fn create_detector() -> EquivocationDetector {
+        EquivocationDetector::new(
+            &Signature::address_of(&operator_key()),
+            Box::new(CoreDb::open("equivocation")),
+        )
+    }
+
+    #[test]
+    fn test_conflicting_roots() {
+        let detector = create_detector();
+        let root1 = SignedRoot::sign(1, &H256::repeat_byte(1), &operator_key()).unwrap();
+        let root2 = SignedRoot::sign(1, &H256::repeat_byte(2), &operator_key()).unwrap();
+        assert_eq!(detector.record_root(&root1).unwrap(), None);
+        assert_eq!(detector.record_root(&root1).unwrap(), None);
+        match detector.check_peer_root(&root2).unwrap() {
+            Some(Equivocation::ConflictingRoots(evidence)) => {
+                assert!(evidence.verify(&Signature::address_of(&operator_key())));
+                assert_eq!(evidence.get_first(), &root1);
+                assert_eq!(evidence.get_second(), &root2);
+            }
+            _ => panic!("equivocation should be detected"),
+        }
+        // the first root is kept
+        assert!(detector.record_root(&root2).unwrap().is_some());
+        assert_eq!(detector.get_root(1).unwrap(), Some(root1));
+    }
+
+    #[test]
+    fn test_reopen() {
+        let dir = TempDir::new("equivocation").unwrap();
+        let open = || {
+            EquivocationDetector::new(
+                &Signature::address_of(&operator_key()),
+                Box::new(KvsLevelImpl::open_path(dir.path()).unwrap()),
+            )
+        };
+        let root1 = SignedRoot::sign(1, &H256::repeat_byte(1), &operator_key()).unwrap();
+        let root2 = SignedRoot::sign(1, &H256::repeat_byte(2), &operator_key()).unwrap();
+        {
+            let detector = open();
+            assert_eq!(detector.record_root(&root1).unwrap(), None);
+        }
+        // the root seen before restart is compared
+        let detector = open();
+        assert_eq!(detector.get_root(1).unwrap(), Some(root1));
+        assert!(detector.record_root(&root2).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_reject_root_signed_by_others() {
+        let detector = create_detector();
+        let other_key = SecretKey::parse(&[2u8; 32]).unwrap();
+        let root = SignedRoot::sign(1, &H256::repeat_byte(1), &other_key).unwrap();
+        assert!(detector.record_root(&root).is_err());
+        assert!(detector.check_peer_root(&root).is_err());
+    }
+
+    #[test]
+    fn test_check_commitment() {
+        let detector = create_detector();
+        let contract = MemoryPlasmaContract::default();
+        let root1 = SignedRoot::sign(1, &H256::repeat_byte(1), &operator_key()).unwrap();
+        let root2 = SignedRoot::sign(2, &H256::repeat_byte(2), &operator_key()).unwrap();
+        assert!(detector.record_root(&root1).is_ok());
+        assert!(detector.record_root(&root2).is_ok());
+        contract.commit_block(1, &H256::repeat_byte(1));
+        contract.commit_block(2, &H256::repeat_byte(3));
+        assert_eq!(detector.check_commitment(&contract, 1).unwrap(), None);
+        assert_eq!(
+            detector.check_commitment(&contract, 2).unwrap(),
+            Some(Equivocation::UncommittedRoot {
+                signed_root: root2,
+                committed_root: H256::repeat_byte(3),
+            })
+        );
+    }
+}
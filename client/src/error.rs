@@ -7,7 +7,10 @@
 
 use ethabi::Error as AbiDecodeError;
 use failure::{Backtrace, Context, Fail};
+use jsonrpc_core_client::RpcError;
+use plasma_core::data_structure::error::Error as PlasmaCoreError;
 use plasma_db::error::Error as PlasmaDbError;
+use rlp::DecoderError;
 use std::fmt;
 use std::fmt::Display;
 use std::io::Error as IoError;
@@ -21,6 +24,20 @@ pub enum ErrorKind {
     AbiDecode,
     #[fail(display = "Plasma Db error")]
     PlasmaDbError,
+    #[fail(display = "Plasma Core error")]
+    PlasmaCoreError,
+    #[fail(display = "Invalid state transition")]
+    InvalidStateTransition,
+    #[fail(display = "Invalid signature")]
+    InvalidSignature,
+    #[fail(display = "Operator RPC error")]
+    Rpc,
+    #[fail(display = "RLP Decode error")]
+    RlpDecode,
+    #[fail(display = "Invalid history proof at block {}", block_number)]
+    InvalidHistoryProof { block_number: u64 },
+    #[fail(display = "History proof of block {} is missing", block_number)]
+    MissingHistoryProof { block_number: u64 },
 }
 
 #[derive(Debug)]
@@ -91,3 +108,27 @@ impl From<PlasmaDbError> for Error {
         }
     }
 }
+
+impl From<PlasmaCoreError> for Error {
+    fn from(error: PlasmaCoreError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::PlasmaCoreError),
+        }
+    }
+}
+
+impl From<DecoderError> for Error {
+    fn from(error: DecoderError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::RlpDecode),
+        }
+    }
+}
+
+impl From<RpcError> for Error {
+    fn from(error: RpcError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::Rpc),
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//
+// Created on Tue Jun 11 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+extern crate ethabi;
+
+use crate::error::{Error, ErrorKind};
+use ethabi::Token;
+use parking_lot::RwLock;
+use plasma_core::data_structure::{InclusionProof, StateUpdate, Transaction};
+use plasma_db::impls::kvs::kvdb::CoreDb;
+use plasma_db::traits::db::DatabaseTrait;
+use plasma_db::traits::kvs::{Batch, KeyValueStore};
+
+/// A self-contained challenge package against an invalid transaction in a committed block.
+/// - has a `prior_state_update` which was spent by the transaction
+/// - has the `prior_transaction` which created the prior state update, and its `prior_inclusion_proof`
+///   (`None` if the prior state update is a deposit)
+/// - has the offending `transaction` and its `inclusion_proof` in `block_number`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FraudProof {
+    prior_state_update: StateUpdate,
+    prior_transaction: Option<Transaction>,
+    prior_inclusion_proof: Option<InclusionProof>,
+    transaction: Transaction,
+    inclusion_proof: InclusionProof,
+    block_number: u64,
+}
+
+impl FraudProof {
+    pub fn new(
+        prior_state_update: &StateUpdate,
+        prior_inclusion: Option<(&Transaction, &InclusionProof)>,
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+        block_number: u64,
+    ) -> Self {
+        FraudProof {
+            prior_state_update: prior_state_update.clone(),
+            prior_transaction: prior_inclusion.map(|(transaction, _)| transaction.clone()),
+            prior_inclusion_proof: prior_inclusion
+                .map(|(_, inclusion_proof)| inclusion_proof.clone()),
+            transaction: transaction.clone(),
+            inclusion_proof: inclusion_proof.clone(),
+            block_number,
+        }
+    }
+    pub fn get_prior_state_update(&self) -> &StateUpdate {
+        &self.prior_state_update
+    }
+    pub fn get_prior_transaction(&self) -> Option<&Transaction> {
+        self.prior_transaction.as_ref()
+    }
+    pub fn get_prior_inclusion_proof(&self) -> Option<&InclusionProof> {
+        self.prior_inclusion_proof.as_ref()
+    }
+    pub fn get_transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+    pub fn get_inclusion_proof(&self) -> &InclusionProof {
+        &self.inclusion_proof
+    }
+    pub fn get_block_number(&self) -> u64 {
+        self.block_number
+    }
+    pub fn to_abi(&self) -> Vec<u8> {
+        ethabi::encode(&[
+            Token::Bytes(self.prior_state_update.to_abi()),
+            Token::Bytes(
+                self.prior_transaction
+                    .as_ref()
+                    .map(Transaction::to_abi)
+                    .unwrap_or_default(),
+            ),
+            Token::Bytes(
+                self.prior_inclusion_proof
+                    .as_ref()
+                    .map(InclusionProof::to_abi)
+                    .unwrap_or_default(),
+            ),
+            Token::Bytes(self.transaction.to_abi()),
+            Token::Bytes(self.inclusion_proof.to_abi()),
+            Token::Uint(self.block_number.into()),
+        ])
+    }
+    pub fn from_abi(data: &[u8]) -> Result<Self, Error> {
+        let decoded: Vec<Token> = ethabi::decode(
+            &[
+                ethabi::ParamType::Bytes,
+                ethabi::ParamType::Bytes,
+                ethabi::ParamType::Bytes,
+                ethabi::ParamType::Bytes,
+                ethabi::ParamType::Bytes,
+                ethabi::ParamType::Uint(8),
+            ],
+            data,
+        )
+        .map_err(|_e| Error::from(ErrorKind::AbiDecode))?;
+        let prior_state_update = decoded[0].clone().to_bytes();
+        let prior_transaction = decoded[1].clone().to_bytes();
+        let prior_inclusion_proof = decoded[2].clone().to_bytes();
+        let transaction = decoded[3].clone().to_bytes();
+        let inclusion_proof = decoded[4].clone().to_bytes();
+        let block_number = decoded[5].clone().to_uint();
+
+        if let (
+            Some(prior_state_update),
+            Some(prior_transaction),
+            Some(prior_inclusion_proof),
+            Some(transaction),
+            Some(inclusion_proof),
+            Some(block_number),
+        ) = (
+            prior_state_update,
+            prior_transaction,
+            prior_inclusion_proof,
+            transaction,
+            inclusion_proof,
+            block_number,
+        ) {
+            let prior_inclusion = match (
+                prior_transaction.is_empty(),
+                prior_inclusion_proof.is_empty(),
+            ) {
+                (true, true) => None,
+                (false, false) => Some((
+                    Transaction::from_abi(&prior_transaction)?,
+                    InclusionProof::from_abi(&prior_inclusion_proof)?,
+                )),
+                _ => return Err(Error::from(ErrorKind::AbiDecode)),
+            };
+            Ok(FraudProof::new(
+                &StateUpdate::from_abi(&prior_state_update)?,
+                prior_inclusion
+                    .as_ref()
+                    .map(|(transaction, inclusion_proof)| (transaction, inclusion_proof)),
+                &Transaction::from_abi(&transaction)?,
+                &InclusionProof::from_abi(&inclusion_proof)?,
+                block_number.as_u64(),
+            ))
+        } else {
+            Err(Error::from(ErrorKind::AbiDecode))
+        }
+    }
+}
+
+/// FraudProofDb persists fraud proofs until they are submitted.
+pub struct FraudProofDb {
+    db: RwLock<Box<dyn KeyValueStore + Send + Sync>>,
+}
+
+impl Default for FraudProofDb {
+    fn default() -> Self {
+        Self::new(Box::new(CoreDb::open("fraud_proof")))
+    }
+}
+
+impl FraudProofDb {
+    const COUNT_KEY: &'static [u8] = b"count";
+
+    /// open a db, which may have fraud proofs written before restart
+    pub fn new(db: Box<dyn KeyValueStore + Send + Sync>) -> Self {
+        FraudProofDb {
+            db: RwLock::new(db),
+        }
+    }
+
+    fn get_count(db: &dyn KeyValueStore) -> Result<u64, Error> {
+        let count = db.get(Self::COUNT_KEY)?;
+        Ok(count.map_or(0, |count| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&count);
+            u64::from_be_bytes(bytes)
+        }))
+    }
+    /// append a fraud proof. The count is read and incremented under the same lock,
+    /// and written with the fraud proof at once, so that concurrent callers never share an index.
+    pub fn put_fraud_proof(&self, fraud_proof: &FraudProof) -> Result<(), Error> {
+        let db = self.db.write();
+        let count = Self::get_count(&**db)?;
+        db.batch(&[
+            Batch::BatchPut {
+                key: &count.to_be_bytes(),
+                value: &fraud_proof.to_abi(),
+            },
+            Batch::BatchPut {
+                key: Self::COUNT_KEY,
+                value: &(count + 1).to_be_bytes(),
+            },
+        ])
+        .map_err(Into::into)
+    }
+    pub fn get_fraud_proofs(&self) -> Result<Vec<FraudProof>, Error> {
+        let db = self.db.read();
+        let count = Self::get_count(&**db)?;
+        let mut fraud_proofs = vec![];
+        for i in 0..count {
+            if let Some(encoded) = db.get(&i.to_be_bytes())? {
+                fraud_proofs.push(FraudProof::from_abi(&encoded)?);
+            }
+        }
+        Ok(fraud_proofs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FraudProof, FraudProofDb};
+    use crate::test_utils::{create_state_update, create_transaction};
+    use ethereum_types::H256;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::process::BlockGenerator;
+    use plasma_db::impls::kvs::leveldb::KvsLevelImpl;
+    use std::sync::Arc;
+    use std::thread;
+    use tempdir::TempDir;
+
+    fn create_fraud_proof(block_number: u64) -> FraudProof {
+        let transaction = create_transaction(0, 100);
+        let block = BlockGenerator::generate(
+            &[transaction.clone()],
+            block_number,
+            &H256::zero(),
+            0,
+            &SecretKey::parse(&[1u8; 32]).unwrap(),
+        )
+        .unwrap();
+        FraudProof::new(
+            &create_state_update(0, 100, 0),
+            None,
+            &transaction,
+            &block.get_inclusion_proof(0).unwrap(),
+            block_number,
+        )
+    }
+
+    #[test]
+    fn test_put_fraud_proofs_concurrently() {
+        let db: Arc<FraudProofDb> = Arc::new(Default::default());
+        let handles: Vec<_> = (1..=8)
+            .map(|block_number| {
+                let db = db.clone();
+                let fraud_proof = create_fraud_proof(block_number);
+                thread::spawn(move || db.put_fraud_proof(&fraud_proof).unwrap())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let mut block_numbers: Vec<u64> = db
+            .get_fraud_proofs()
+            .unwrap()
+            .iter()
+            .map(FraudProof::get_block_number)
+            .collect();
+        block_numbers.sort();
+        assert_eq!(block_numbers, (1..=8).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_abi_with_prior_transaction() {
+        let prior = create_fraud_proof(1);
+        let fraud_proof = FraudProof::new(
+            &create_state_update(0, 100, 1),
+            Some((prior.get_transaction(), prior.get_inclusion_proof())),
+            &create_transaction(0, 50),
+            prior.get_inclusion_proof(),
+            2,
+        );
+        let decoded = FraudProof::from_abi(&fraud_proof.to_abi()).unwrap();
+        assert_eq!(decoded, fraud_proof);
+        assert_eq!(
+            decoded.get_prior_transaction(),
+            Some(prior.get_transaction())
+        );
+    }
+
+    #[test]
+    fn test_reopen() {
+        let dir = TempDir::new("fraud_proof").unwrap();
+        let open = || FraudProofDb::new(Box::new(KvsLevelImpl::open_path(dir.path()).unwrap()));
+        {
+            let db = open();
+            assert!(db.put_fraud_proof(&create_fraud_proof(1)).is_ok());
+        }
+        let db = open();
+        assert!(db.put_fraud_proof(&create_fraud_proof(2)).is_ok());
+        assert_eq!(
+            db.get_fraud_proofs().unwrap(),
+            vec![create_fraud_proof(1), create_fraud_proof(2)]
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//
+// Created on Wed Jun 26 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use crate::error::{Error, ErrorKind};
+use ethabi::Token;
+use ethereum_types::{Address, H256};
+use plasma_core::data_structure::{BlockHeader, InclusionProof, StateUpdate, Transaction};
+use plasma_core::process::StateVerifier;
+use predicate_plugins::PredicateManager;
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+
+/// A transaction of a block and its inclusion proof.
+/// If the transaction doesn't touch the range, it proves that the range is untouched
+/// in the block by the implicit bounds of its leaf.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofElement {
+    block_number: u64,
+    transaction: Transaction,
+    inclusion_proof: InclusionProof,
+}
+
+impl ProofElement {
+    pub fn new(
+        block_number: u64,
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+    ) -> Self {
+        ProofElement {
+            block_number,
+            transaction: transaction.clone(),
+            inclusion_proof: inclusion_proof.clone(),
+        }
+    }
+    pub fn get_block_number(&self) -> u64 {
+        self.block_number
+    }
+    pub fn get_transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+    pub fn get_inclusion_proof(&self) -> &InclusionProof {
+        &self.inclusion_proof
+    }
+}
+
+/// History of a range from its deposit.
+/// It is encoded by ABI as `(bytes deposit, uint256[] blockNumbers, bytes[] transactions, bytes[] inclusionProofs)`.
+/// The encoding is specific to this crate, and is not compatible with the history proof of the JS client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryProof {
+    deposit: StateUpdate,
+    elements: Vec<ProofElement>,
+}
+
+impl HistoryProof {
+    pub fn new(deposit: &StateUpdate, elements: &[ProofElement]) -> Self {
+        HistoryProof {
+            deposit: deposit.clone(),
+            elements: elements.to_vec(),
+        }
+    }
+    pub fn get_deposit(&self) -> &StateUpdate {
+        &self.deposit
+    }
+    pub fn get_elements(&self) -> &[ProofElement] {
+        &self.elements
+    }
+    pub fn to_abi(&self) -> Vec<u8> {
+        ethabi::encode(&[
+            Token::Bytes(self.deposit.to_abi()),
+            Token::Array(
+                self.elements
+                    .iter()
+                    .map(|element| Token::Uint(element.block_number.into()))
+                    .collect(),
+            ),
+            Token::Array(
+                self.elements
+                    .iter()
+                    .map(|element| Token::Bytes(element.transaction.to_abi()))
+                    .collect(),
+            ),
+            Token::Array(
+                self.elements
+                    .iter()
+                    .map(|element| Token::Bytes(element.inclusion_proof.to_abi()))
+                    .collect(),
+            ),
+        ])
+    }
+    pub fn from_abi(data: &[u8]) -> Result<Self, Error> {
+        let decoded: Vec<Token> = ethabi::decode(
+            &[
+                ethabi::ParamType::Bytes,
+                ethabi::ParamType::Array(Box::new(ethabi::ParamType::Uint(256))),
+                ethabi::ParamType::Array(Box::new(ethabi::ParamType::Bytes)),
+                ethabi::ParamType::Array(Box::new(ethabi::ParamType::Bytes)),
+            ],
+            data,
+        )
+        .map_err(|_e| Error::from(ErrorKind::AbiDecode))?;
+        let deposit = decoded[0].clone().to_bytes();
+        let block_numbers = decoded[1].clone().to_array();
+        let transactions = decoded[2].clone().to_array();
+        let inclusion_proofs = decoded[3].clone().to_array();
+        if let (Some(deposit), Some(block_numbers), Some(transactions), Some(inclusion_proofs)) =
+            (deposit, block_numbers, transactions, inclusion_proofs)
+        {
+            if block_numbers.len() != transactions.len()
+                || block_numbers.len() != inclusion_proofs.len()
+            {
+                return Err(Error::from(ErrorKind::AbiDecode));
+            }
+            let mut elements = vec![];
+            for ((block_number, transaction), inclusion_proof) in block_numbers
+                .into_iter()
+                .zip(transactions.into_iter())
+                .zip(inclusion_proofs.into_iter())
+            {
+                if let (Some(block_number), Some(transaction), Some(inclusion_proof)) = (
+                    block_number.to_uint(),
+                    transaction.to_bytes(),
+                    inclusion_proof.to_bytes(),
+                ) {
+                    elements.push(ProofElement::new(
+                        block_number.as_u64(),
+                        &Transaction::from_abi(&transaction)?,
+                        &InclusionProof::from_abi(&inclusion_proof)?,
+                    ));
+                } else {
+                    return Err(Error::from(ErrorKind::AbiDecode));
+                }
+            }
+            Ok(HistoryProof::new(
+                &StateUpdate::from_abi(&deposit)?,
+                &elements,
+            ))
+        } else {
+            Err(Error::from(ErrorKind::AbiDecode))
+        }
+    }
+}
+
+/// Returns true if `ranges` cover `[start, end)` without a gap
+fn covers(ranges: &mut Vec<(u64, u64)>, start: u64, end: u64) -> bool {
+    ranges.retain(|(range_start, range_end)| range_start < range_end);
+    ranges.sort();
+    let mut covered = start;
+    for (range_start, range_end) in ranges.iter() {
+        if *range_start > covered {
+            break;
+        }
+        covered = max(covered, *range_end);
+    }
+    covered >= end
+}
+
+/// Parts of `state_update` outside of `[start, end)`
+fn subtract(state_update: &StateUpdate, start: u64, end: u64) -> Vec<StateUpdate> {
+    [
+        (state_update.get_start(), min(start, state_update.get_end())),
+        (max(end, state_update.get_start()), state_update.get_end()),
+    ]
+    .iter()
+    .filter(|(part_start, part_end)| part_start < part_end)
+    .map(|(part_start, part_end)| {
+        StateUpdate::new(
+            state_update.get_state_object(),
+            *part_start,
+            *part_end,
+            state_update.get_block_number(),
+            *state_update.get_plasma_contract(),
+        )
+    })
+    .collect()
+}
+
+/// HistoryVerifier verifies the history of a range from its deposit
+/// against roots of blocks which are already trusted.
+#[derive(Default)]
+pub struct HistoryVerifier {
+    roots: BTreeMap<u64, H256>,
+}
+
+impl HistoryVerifier {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Trust the root of a block header signed by `operator`
+    pub fn add_header(&mut self, header: &BlockHeader, operator: &Address) -> Result<(), Error> {
+        header.verify(operator)?;
+        self.add_root(header.get_number(), header.get_root());
+        Ok(())
+    }
+    /// Trust the root of a block, such as one submitted to the plasma contract
+    pub fn add_root(&mut self, block_number: u64, root: &H256) {
+        self.roots.insert(block_number, *root);
+    }
+
+    /// Verify the history of a deposited range until the latest trusted block.
+    /// Every block after the deposit should have a trusted root,
+    /// and proof elements which cover the range unless the block is empty.
+    /// Proof elements of a block shouldn't overlap each other.
+    /// Returns the current state updates of the range, one for each part which has a different history.
+    pub fn verify(&self, history_proof: &HistoryProof) -> Result<Vec<StateUpdate>, Error> {
+        let deposit = history_proof.get_deposit();
+        let (start, end) = (deposit.get_start(), deposit.get_end());
+        if let Some(element) = history_proof.get_elements().iter().find(|element| {
+            element.get_block_number() <= deposit.get_block_number()
+                || !self.roots.contains_key(&element.get_block_number())
+        }) {
+            return Err(Error::from(ErrorKind::InvalidHistoryProof {
+                block_number: element.get_block_number(),
+            }));
+        }
+        let latest_block_number = match self.roots.keys().next_back() {
+            Some(&latest_block_number) => latest_block_number,
+            None => return Ok(vec![deposit.clone()]),
+        };
+        let mut current_state_updates = vec![deposit.clone()];
+        for block_number in deposit.get_block_number() + 1..=latest_block_number {
+            // a block without a trusted root can't prove that the range is untouched
+            let root = self
+                .roots
+                .get(&block_number)
+                .ok_or_else(|| Error::from(ErrorKind::MissingHistoryProof { block_number }))?;
+            let invalid = || Error::from(ErrorKind::InvalidHistoryProof { block_number });
+            let elements: Vec<&ProofElement> = history_proof
+                .get_elements()
+                .iter()
+                .filter(|element| element.get_block_number() == block_number)
+                .collect();
+            if elements.is_empty() {
+                if root.is_zero() {
+                    continue;
+                }
+                return Err(Error::from(ErrorKind::MissingHistoryProof { block_number }));
+            }
+            let mut leaves: Vec<(u64, u64)> = vec![];
+            let mut covered = vec![];
+            let mut new_state_updates = vec![];
+            for element in elements.iter() {
+                let transaction = element.get_transaction();
+                let (implicit_start, implicit_end) = StateVerifier::verify_implicit_bounds(
+                    transaction,
+                    element.get_inclusion_proof(),
+                    root,
+                )
+                .map_err(|_| invalid())?;
+                // leaves of a block don't overlap, so an overlapping element is a duplicate
+                if leaves.iter().any(|(leaf_start, leaf_end)| {
+                    *leaf_start < implicit_end && implicit_start < *leaf_end
+                }) {
+                    return Err(invalid());
+                }
+                leaves.push((implicit_start, implicit_end));
+                // the range which the leaf covers but the transaction doesn't touch
+                covered.push((
+                    max(implicit_start, start),
+                    min(transaction.get_start(), end),
+                ));
+                covered.push((max(transaction.get_end(), start), min(implicit_end, end)));
+                let touched_start = max(transaction.get_start(), start);
+                let touched_end = min(transaction.get_end(), end);
+                if touched_start >= touched_end {
+                    continue;
+                }
+                covered.push((touched_start, touched_end));
+                for prior_state_update in current_state_updates.iter().filter(|state_update| {
+                    state_update.get_start() < touched_end && touched_start < state_update.get_end()
+                }) {
+                    StateVerifier::verify_transition(prior_state_update, transaction)
+                        .map_err(|_| invalid())?;
+                    let new_state_update = PredicateManager::get_plugin(
+                        prior_state_update.get_state_object().get_predicate(),
+                    )
+                    .execute_state_transition(prior_state_update, transaction)
+                    .map_err(|_| invalid())?;
+                    new_state_updates.push(StateUpdate::new(
+                        new_state_update.get_state_object(),
+                        max(prior_state_update.get_start(), touched_start),
+                        min(prior_state_update.get_end(), touched_end),
+                        block_number,
+                        *new_state_update.get_plasma_contract(),
+                    ));
+                }
+            }
+            if !covers(&mut covered, start, end) {
+                return Err(invalid());
+            }
+            for new_state_update in new_state_updates.iter() {
+                current_state_updates = current_state_updates
+                    .iter()
+                    .flat_map(|state_update| {
+                        subtract(
+                            state_update,
+                            new_state_update.get_start(),
+                            new_state_update.get_end(),
+                        )
+                    })
+                    .collect();
+            }
+            current_state_updates.extend(new_state_updates);
+            current_state_updates.sort_by_key(StateUpdate::get_start);
+        }
+        Ok(current_state_updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryProof, HistoryVerifier, ProofElement};
+    use crate::error::ErrorKind;
+    use crate::test_utils::{create_state_update, create_transaction};
+    use ethereum_types::H256;
+    use plasma_core::data_structure::signature::{SecretKey, Signature};
+    use plasma_core::data_structure::Block;
+    use plasma_core::process::BlockGenerator;
+
+    /// blocks 1 to 3, and a verifier which trusts them
+    fn create_blocks() -> (Vec<Block>, HistoryVerifier) {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let transactions = vec![
+            vec![create_transaction(0, 50)],
+            vec![create_transaction(100, 150), create_transaction(150, 200)],
+            vec![],
+        ];
+        let mut verifier = HistoryVerifier::new();
+        let blocks: Vec<Block> = transactions
+            .iter()
+            .enumerate()
+            .map(|(i, transactions)| {
+                BlockGenerator::generate(
+                    transactions,
+                    i as u64 + 1,
+                    &H256::zero(),
+                    0,
+                    &operator_key,
+                )
+                .unwrap()
+            })
+            .collect();
+        for block in blocks.iter() {
+            assert!(verifier
+                .add_header(block.get_header(), &Signature::address_of(&operator_key))
+                .is_ok());
+        }
+        (blocks, verifier)
+    }
+
+    fn create_element(block: &Block, index: usize) -> ProofElement {
+        ProofElement::new(
+            block.get_number(),
+            &block.get_transactions()[index],
+            &block.get_inclusion_proof(index).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_verify_history() {
+        let (blocks, verifier) = create_blocks();
+        let history_proof = HistoryProof::new(
+            &create_state_update(0, 100, 0),
+            &[create_element(&blocks[0], 0), create_element(&blocks[1], 0)],
+        );
+        assert_eq!(
+            verifier.verify(&history_proof).unwrap(),
+            vec![
+                create_state_update(0, 50, 1),
+                create_state_update(50, 100, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_history_proof() {
+        let (blocks, verifier) = create_blocks();
+        let history_proof = HistoryProof::new(
+            &create_state_update(0, 100, 0),
+            &[create_element(&blocks[0], 0)],
+        );
+        match verifier.verify(&history_proof).unwrap_err().kind() {
+            ErrorKind::MissingHistoryProof { block_number } => assert_eq!(*block_number, 2),
+            _ => panic!("block 2 should be missing"),
+        }
+    }
+
+    #[test]
+    fn test_missing_trusted_root() {
+        let (blocks, _) = create_blocks();
+        let mut verifier = HistoryVerifier::new();
+        verifier.add_root(1, blocks[0].get_root());
+        verifier.add_root(3, blocks[2].get_root());
+        let history_proof = HistoryProof::new(
+            &create_state_update(0, 100, 0),
+            &[create_element(&blocks[0], 0)],
+        );
+        match verifier.verify(&history_proof).unwrap_err().kind() {
+            ErrorKind::MissingHistoryProof { block_number } => assert_eq!(*block_number, 2),
+            _ => panic!("root of block 2 should be missing"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_exclusion_proof() {
+        let (blocks, verifier) = create_blocks();
+        // the second leaf covers only [150, 200)
+        let history_proof = HistoryProof::new(
+            &create_state_update(0, 100, 0),
+            &[create_element(&blocks[0], 0), create_element(&blocks[1], 1)],
+        );
+        match verifier.verify(&history_proof).unwrap_err().kind() {
+            ErrorKind::InvalidHistoryProof { block_number } => assert_eq!(*block_number, 2),
+            _ => panic!("exclusion proof of block 2 should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_proof_element() {
+        let (blocks, verifier) = create_blocks();
+        let history_proof = HistoryProof::new(
+            &create_state_update(0, 100, 0),
+            &[
+                create_element(&blocks[0], 0),
+                create_element(&blocks[0], 0),
+                create_element(&blocks[1], 0),
+            ],
+        );
+        match verifier.verify(&history_proof).unwrap_err().kind() {
+            ErrorKind::InvalidHistoryProof { block_number } => assert_eq!(*block_number, 1),
+            _ => panic!("duplicate proof element of block 1 should be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_history_proof_abi() {
+        let (blocks, _) = create_blocks();
+        let history_proof = HistoryProof::new(
+            &create_state_update(0, 100, 0),
+            &[create_element(&blocks[0], 0), create_element(&blocks[1], 0)],
+        );
+        assert_eq!(
+            HistoryProof::from_abi(&history_proof.to_abi()).unwrap(),
+            history_proof
+        );
+    }
+}
@@ -1,2 +1,10 @@
+pub mod contract;
+pub mod equivocation;
 pub mod error;
+pub mod fraud_proof;
+pub mod history;
 pub mod state;
+pub mod sync;
+#[cfg(test)]
+pub mod test_utils;
+pub mod watchtower;
@@ -1,6 +1,8 @@
 pub mod state_db;
 pub mod state_manager;
+pub mod sync_db;
 
 pub use self::state_db::StateDb;
 pub use self::state_db::VerifiedStateUpdate;
 pub use self::state_manager::StateManager;
+pub use self::sync_db::{SyncDb, SyncedRange};
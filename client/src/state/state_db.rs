@@ -2,7 +2,7 @@ extern crate ethabi;
 
 use crate::error::{Error, ErrorKind};
 use ethabi::Token;
-use plasma_core::data_structure::StateUpdate;
+use plasma_core::data_structure::{InclusionProof, StateUpdate, Transaction};
 use plasma_db::impls::rangestore::memory::RangeDbMemoryImpl;
 use plasma_db::traits::db::DatabaseTrait;
 use plasma_db::traits::rangestore::RangeStore;
@@ -13,6 +13,8 @@ pub struct VerifiedStateUpdate {
     end: u64,
     verified_block_number: u64,
     state_update: StateUpdate,
+    transaction: Option<Transaction>,
+    inclusion_proof: Option<InclusionProof>,
 }
 
 impl VerifiedStateUpdate {
@@ -27,6 +29,26 @@ impl VerifiedStateUpdate {
             end,
             verified_block_number,
             state_update: state_update.clone(),
+            transaction: None,
+            inclusion_proof: None,
+        }
+    }
+    /// state update which was verified by the inclusion proof of the transaction creating it
+    pub fn from_included(
+        start: u64,
+        end: u64,
+        verified_block_number: u64,
+        state_update: &StateUpdate,
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+    ) -> Self {
+        VerifiedStateUpdate {
+            start,
+            end,
+            verified_block_number,
+            state_update: state_update.clone(),
+            transaction: Some(transaction.clone()),
+            inclusion_proof: Some(inclusion_proof.clone()),
         }
     }
     pub fn from(verified_block_number: u64, state_update: &StateUpdate) -> Self {
@@ -35,6 +57,8 @@ impl VerifiedStateUpdate {
             end: state_update.get_end(),
             verified_block_number,
             state_update: state_update.clone(),
+            transaction: None,
+            inclusion_proof: None,
         }
     }
     pub fn get_start(&self) -> u64 {
@@ -46,12 +70,31 @@ impl VerifiedStateUpdate {
     pub fn get_state_update(&self) -> &StateUpdate {
         &self.state_update
     }
+    /// the transaction creating the state update, which is proven by the inclusion proof
+    pub fn get_transaction(&self) -> Option<&Transaction> {
+        self.transaction.as_ref()
+    }
+    pub fn get_inclusion_proof(&self) -> Option<&InclusionProof> {
+        self.inclusion_proof.as_ref()
+    }
     pub fn to_abi(&self) -> Vec<u8> {
         ethabi::encode(&[
             Token::Uint(self.start.into()),
             Token::Uint(self.end.into()),
             Token::Uint(self.verified_block_number.into()),
             Token::Bytes(self.state_update.to_abi()),
+            Token::Bytes(
+                self.transaction
+                    .as_ref()
+                    .map(Transaction::to_abi)
+                    .unwrap_or_default(),
+            ),
+            Token::Bytes(
+                self.inclusion_proof
+                    .as_ref()
+                    .map(InclusionProof::to_abi)
+                    .unwrap_or_default(),
+            ),
         ])
     }
     pub fn from_abi(data: &[u8]) -> Result<Self, Error> {
@@ -61,24 +104,52 @@ impl VerifiedStateUpdate {
                 ethabi::ParamType::Uint(8),
                 ethabi::ParamType::Uint(8),
                 ethabi::ParamType::Bytes,
+                ethabi::ParamType::Bytes,
+                ethabi::ParamType::Bytes,
             ],
             data,
         )
         .map_err(|_e| Error::from(ErrorKind::AbiDecode))?;
-        let block_number = decoded[0].clone().to_uint();
-        let start = decoded[1].clone().to_uint();
-        let end = decoded[2].clone().to_uint();
+        let start = decoded[0].clone().to_uint();
+        let end = decoded[1].clone().to_uint();
+        let block_number = decoded[2].clone().to_uint();
         let state_update = decoded[3].clone().to_bytes();
+        let transaction = decoded[4].clone().to_bytes();
+        let inclusion_proof = decoded[5].clone().to_bytes();
 
-        if let (Some(block_number), Some(start), Some(end), Some(state_update)) =
-            (block_number, start, end, state_update)
-        {
-            Ok(VerifiedStateUpdate::new(
-                block_number.as_u64(),
-                start.as_u64(),
-                end.as_u64(),
-                &StateUpdate::from_abi(&state_update).unwrap(),
-            ))
+        if let (
+            Some(start),
+            Some(end),
+            Some(block_number),
+            Some(state_update),
+            Some(transaction),
+            Some(inclusion_proof),
+        ) = (
+            start,
+            end,
+            block_number,
+            state_update,
+            transaction,
+            inclusion_proof,
+        ) {
+            let transaction = if transaction.is_empty() {
+                None
+            } else {
+                Some(Transaction::from_abi(&transaction)?)
+            };
+            let inclusion_proof = if inclusion_proof.is_empty() {
+                None
+            } else {
+                Some(InclusionProof::from_abi(&inclusion_proof)?)
+            };
+            Ok(VerifiedStateUpdate {
+                start: start.as_u64(),
+                end: end.as_u64(),
+                verified_block_number: block_number.as_u64(),
+                state_update: StateUpdate::from_abi(&state_update)?,
+                transaction,
+                inclusion_proof,
+            })
         } else {
             Err(Error::from(ErrorKind::AbiDecode))
         }
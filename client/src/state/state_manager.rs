@@ -1,8 +1,13 @@
-use crate::error::Error;
+use crate::error::{Error, ErrorKind};
+use crate::fraud_proof::{FraudProof, FraudProofDb};
 use crate::state::{StateDb, VerifiedStateUpdate};
-use ethereum_types::Address;
-use plasma_core::data_structure::{StateUpdate, Transaction};
+use ethereum_types::{Address, H256};
+use futures::future::{self, Either, Future};
+use plasma_core::data_structure::{InclusionProof, InclusionReceipt, StateUpdate, Transaction};
+use plasma_core::process::StateVerifier;
+use plasma_rpc::client::OperatorClient;
 use predicate_plugins::PredicateManager;
+use std::cmp::{max, min};
 
 pub struct ResultOfExecuteTransaction {
     state_update: Box<StateUpdate>,
@@ -26,17 +31,23 @@ impl ResultOfExecuteTransaction {
 
 pub struct StateManager {
     db: Box<StateDb>,
+    fraud_proof_db: FraudProofDb,
 }
 
 impl Default for StateManager {
     fn default() -> Self {
-        Self {
-            db: Default::default(),
-        }
+        Self::new(Default::default(), Default::default())
     }
 }
 
 impl StateManager {
+    /// `fraud_proof_db` should be persistent, so that fraud proofs are submitted after restart
+    pub fn new(db: StateDb, fraud_proof_db: FraudProofDb) -> Self {
+        StateManager {
+            db: Box::new(db),
+            fraud_proof_db,
+        }
+    }
     /// force to put state update
     pub fn deposit(&self, start: u64, end: u64, state_update: &StateUpdate) -> Result<(), Error> {
         self.db
@@ -61,7 +72,7 @@ impl StateManager {
                 PredicateManager::get_plugin(predicate_address)
                     .execute_state_transition(verified_state_update.get_state_update(), transaction)
             })
-            .collect();
+            .collect::<Result<Vec<StateUpdate>, _>>()?;
         // new_state_updates should has same state_update
         let new_state_update: &StateUpdate = &new_state_updates[0];
         self.db
@@ -74,37 +85,128 @@ impl StateManager {
             &verified_state_updates,
         ))
     }
+
+    /// Execute a transaction, and send it to the operator.
+    /// The transaction is sent only if it is valid on the local state.
+    pub fn send_transaction(
+        &self,
+        operator: &OperatorClient,
+        transaction: &Transaction,
+    ) -> impl Future<Item = InclusionReceipt, Error = Error> {
+        match self.execute_transaction(transaction) {
+            Ok(_) => Either::A(operator.send_transaction(transaction).map_err(Error::from)),
+            Err(err) => Either::B(future::err(err)),
+        }
+    }
+
+    /// Apply a transaction included in a committed block.
+    /// If the transaction is an invalid transition, a fraud proof is persisted
+    /// and `ErrorKind::InvalidStateTransition` is returned.
+    pub fn apply_included_transaction(
+        &self,
+        block_number: u64,
+        root: &H256,
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+    ) -> Result<Box<[VerifiedStateUpdate]>, Error> {
+        StateVerifier::verify_inclusion(transaction, inclusion_proof, root)?;
+        let verified_state_updates = self
+            .db
+            .get_verified_state_updates(transaction.get_start(), transaction.get_end())?;
+        let mut new_verified_state_updates = vec![];
+        for verified_state_update in verified_state_updates.iter() {
+            let prior_state_update = verified_state_update.get_state_update();
+            let predicate_address: &Address = prior_state_update.get_state_object().get_predicate();
+            let result = StateVerifier::verify_transition(prior_state_update, transaction)
+                .and_then(|()| {
+                    PredicateManager::get_plugin(predicate_address)
+                        .execute_state_transition(prior_state_update, transaction)
+                });
+            match result {
+                Ok(new_state_update) => {
+                    let start = max(verified_state_update.get_start(), transaction.get_start());
+                    let end = min(verified_state_update.get_end(), transaction.get_end());
+                    new_verified_state_updates.push(VerifiedStateUpdate::from_included(
+                        start,
+                        end,
+                        block_number,
+                        &StateUpdate::new(
+                            new_state_update.get_state_object(),
+                            start,
+                            end,
+                            block_number,
+                            *new_state_update.get_plasma_contract(),
+                        ),
+                        transaction,
+                        inclusion_proof,
+                    ))
+                }
+                Err(_) => {
+                    let prior_inclusion =
+                        verified_state_update
+                            .get_transaction()
+                            .and_then(|prior_transaction| {
+                                verified_state_update.get_inclusion_proof().map(
+                                    |prior_inclusion_proof| {
+                                        (prior_transaction, prior_inclusion_proof)
+                                    },
+                                )
+                            });
+                    self.fraud_proof_db.put_fraud_proof(&FraudProof::new(
+                        prior_state_update,
+                        prior_inclusion,
+                        transaction,
+                        inclusion_proof,
+                        block_number,
+                    ))?;
+                    return Err(Error::from(ErrorKind::InvalidStateTransition));
+                }
+            }
+        }
+        for new_verified_state_update in new_verified_state_updates.iter() {
+            self.db
+                .put_verified_state_update(new_verified_state_update)?;
+        }
+        Ok(new_verified_state_updates.into_boxed_slice())
+    }
+
+    /// Verified state updates between start and end
+    pub fn get_verified_state_updates(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Box<[VerifiedStateUpdate]>, Error> {
+        self.db.get_verified_state_updates(start, end)
+    }
+
+    /// Fraud proofs which are waiting for submission
+    pub fn get_fraud_proofs(&self) -> Result<Vec<FraudProof>, Error> {
+        self.fraud_proof_db.get_fraud_proofs()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::StateManager;
+    use crate::error::Error;
+    use crate::test_utils::{create_state_update, create_transaction, create_transaction_of};
     use ethereum_types::{Address, H256};
-    use plasma_core::data_structure::{StateObject, StateUpdate, Transaction, Witness};
-
-    fn create_state_update(start: u64, end: u64, block_number: u64) -> StateUpdate {
-        StateUpdate::new(
-            &StateObject::new(Address::zero(), &b"data"[..]),
-            start,
-            end,
-            block_number,
-            Address::zero(),
-        )
-    }
+    use futures::Future;
+    use jsonrpc_core::IoHandler;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::data_structure::{Block, InclusionReceipt};
+    use plasma_core::process::BlockGenerator;
+    use plasma_operator::context::ChainContext;
+    use plasma_operator::rpc::plasmarpcimpl::PlasmaRpcImpl;
+    use plasma_rpc::client::OperatorClient;
+    use plasma_rpc::plasmarpc::PlasmaRpc;
 
     #[test]
     fn test_execute_transaction() {
         // make state update
         let state_update = create_state_update(0, 100, 1);
         // make transaction
-        let transaction = Transaction::new(
-            Address::zero(),
-            0,
-            100,
-            Transaction::create_method_id(&b"send(address)"[..]),
-            &b"new state update"[..],
-            &Witness::new(H256::zero(), H256::zero(), 0),
-        );
+        let transaction = create_transaction(0, 100);
 
         let state_manager: StateManager = Default::default();
         let deposit_result = state_manager.deposit(0, 100, &state_update);
@@ -118,14 +220,7 @@ mod tests {
         // make state update
         let state_update = create_state_update(0, 100, 1);
         // make transaction
-        let transaction = Transaction::new(
-            Address::zero(),
-            0,
-            20,
-            Transaction::create_method_id(&b"send(address)"[..]),
-            &b"new state update"[..],
-            &Witness::new(H256::zero(), H256::zero(), 0),
-        );
+        let transaction = create_transaction(0, 20);
 
         let state_manager: StateManager = Default::default();
         let deposit_result = state_manager.deposit(0, 100, &state_update);
@@ -140,14 +235,7 @@ mod tests {
         let state_update1 = create_state_update(0, 100, 1);
         let state_update2 = create_state_update(100, 200, 2);
         // make transaction
-        let transaction = Transaction::new(
-            Address::zero(),
-            50,
-            150,
-            Transaction::create_method_id(&b"send(address)"[..]),
-            &b"new state update"[..],
-            &Witness::new(H256::zero(), H256::zero(), 0),
-        );
+        let transaction = create_transaction(50, 150);
 
         let state_manager: StateManager = Default::default();
         assert!(state_manager.deposit(0, 100, &state_update1).is_ok());
@@ -156,4 +244,142 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_send_transaction() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let chain_context = ChainContext::new(&operator_key);
+        let deposit = create_state_update(0, 100, 0);
+        assert!(chain_context.deposit(&deposit).is_ok());
+        let state_manager: StateManager = Default::default();
+        assert!(state_manager.deposit(0, 100, &deposit).is_ok());
+
+        let mut io = IoHandler::new();
+        io.extend_with(PlasmaRpcImpl::from_chain_context(chain_context.clone()).to_delegate());
+        let (operator, rpc_client) = OperatorClient::connect_local(io);
+        let transaction = create_transaction(0, 50);
+        let future = state_manager.send_transaction(&operator, &transaction);
+        drop(operator);
+        let (receipt, ()) = future.join(rpc_client.map_err(Error::from)).wait().unwrap();
+        assert_eq!(
+            receipt,
+            InclusionReceipt::sign(&transaction.hash(), 1, &operator_key).unwrap()
+        );
+        assert_eq!(chain_context.get_pending_transaction_count(), 1);
+    }
+
+    #[test]
+    fn test_apply_included_transaction() {
+        let state_manager: StateManager = Default::default();
+        assert!(state_manager
+            .deposit(0, 100, &create_state_update(0, 100, 1))
+            .is_ok());
+        let transactions = vec![create_transaction(0, 50), create_transaction(100, 200)];
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let block =
+            BlockGenerator::generate(&transactions, 2, &H256::zero(), 0, &operator_key).unwrap();
+        let result = state_manager.apply_included_transaction(
+            2,
+            block.get_root(),
+            &transactions[0],
+            &block.get_inclusion_proof(0).unwrap(),
+        );
+        assert_eq!(result.unwrap()[0].get_state_update().get_block_number(), 2);
+        assert!(state_manager.get_fraud_proofs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_invalid_transaction_creates_fraud_proof() {
+        let state_manager: StateManager = Default::default();
+        let deposit = create_state_update(0, 100, 1);
+        assert!(state_manager.deposit(0, 100, &deposit).is_ok());
+        // the transaction is for another plasma contract
+        let transactions = vec![create_transaction_of(Address::from_low_u64_be(1), 0, 50)];
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let block =
+            BlockGenerator::generate(&transactions, 2, &H256::zero(), 0, &operator_key).unwrap();
+        let inclusion_proof = block.get_inclusion_proof(0).unwrap();
+        let result = state_manager.apply_included_transaction(
+            2,
+            block.get_root(),
+            &transactions[0],
+            &inclusion_proof,
+        );
+        assert!(result.is_err());
+        let fraud_proofs = state_manager.get_fraud_proofs().unwrap();
+        assert_eq!(fraud_proofs.len(), 1);
+        assert_eq!(fraud_proofs[0].get_prior_state_update(), &deposit);
+        assert!(fraud_proofs[0].get_prior_inclusion_proof().is_none());
+        assert_eq!(fraud_proofs[0].get_transaction(), &transactions[0]);
+        assert_eq!(fraud_proofs[0].get_inclusion_proof(), &inclusion_proof);
+    }
+
+    #[test]
+    fn test_fraud_proof_has_prior_transaction() {
+        let state_manager: StateManager = Default::default();
+        assert!(state_manager
+            .deposit(0, 100, &create_state_update(0, 100, 1))
+            .is_ok());
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let prior_transaction = create_transaction(0, 50);
+        let prior_block = BlockGenerator::generate(
+            &[prior_transaction.clone()],
+            2,
+            &H256::zero(),
+            0,
+            &operator_key,
+        )
+        .unwrap();
+        let prior_inclusion_proof = prior_block.get_inclusion_proof(0).unwrap();
+        assert!(state_manager
+            .apply_included_transaction(
+                2,
+                prior_block.get_root(),
+                &prior_transaction,
+                &prior_inclusion_proof
+            )
+            .is_ok());
+        let transaction = create_transaction_of(Address::from_low_u64_be(1), 0, 50);
+        let block =
+            BlockGenerator::generate(&[transaction.clone()], 3, &H256::zero(), 0, &operator_key)
+                .unwrap();
+        assert!(state_manager
+            .apply_included_transaction(
+                3,
+                block.get_root(),
+                &transaction,
+                &block.get_inclusion_proof(0).unwrap()
+            )
+            .is_err());
+        let fraud_proofs = state_manager.get_fraud_proofs().unwrap();
+        assert_eq!(fraud_proofs.len(), 1);
+        // the prior transaction is proven by the prior inclusion proof in the package
+        assert_eq!(
+            fraud_proofs[0].get_prior_transaction(),
+            Some(&prior_transaction)
+        );
+        assert!(fraud_proofs[0].get_prior_inclusion_proof().unwrap().verify(
+            &Block::create_leaf(&prior_transaction),
+            prior_block.get_root()
+        ));
+    }
+
+    #[test]
+    fn test_apply_transaction_with_invalid_inclusion_proof() {
+        let state_manager: StateManager = Default::default();
+        assert!(state_manager
+            .deposit(0, 100, &create_state_update(0, 100, 1))
+            .is_ok());
+        let transactions = vec![create_transaction(0, 50), create_transaction(100, 200)];
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let block =
+            BlockGenerator::generate(&transactions, 2, &H256::zero(), 0, &operator_key).unwrap();
+        let result = state_manager.apply_included_transaction(
+            2,
+            block.get_root(),
+            &transactions[0],
+            &block.get_inclusion_proof(1).unwrap(),
+        );
+        assert!(result.is_err());
+        assert!(state_manager.get_fraud_proofs().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,242 @@
+use crate::error::Error;
+use parking_lot::RwLock;
+use plasma_core::data_structure::BlockHeader;
+use plasma_db::impls::kvs::kvdb::CoreDb;
+use plasma_db::traits::db::DatabaseTrait;
+use plasma_db::traits::kvs::{Batch, KeyValueStore};
+use std::cmp::{max, min};
+
+/// A range which is watched, and the last block verified for it
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncedRange {
+    start: u64,
+    end: u64,
+    block_number: u64,
+}
+
+impl SyncedRange {
+    pub fn new(start: u64, end: u64, block_number: u64) -> Self {
+        SyncedRange {
+            start,
+            end,
+            block_number,
+        }
+    }
+    pub fn get_start(&self) -> u64 {
+        self.start
+    }
+    pub fn get_end(&self) -> u64 {
+        self.end
+    }
+    pub fn get_block_number(&self) -> u64 {
+        self.block_number
+    }
+}
+
+fn encode_ranges(ranges: &[SyncedRange]) -> Vec<u8> {
+    let mut bytes = vec![];
+    for range in ranges.iter() {
+        for value in [range.start, range.end, range.block_number].iter() {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    bytes
+}
+
+fn decode_ranges(bytes: &[u8]) -> Vec<SyncedRange> {
+    let values: Vec<u64> = bytes
+        .chunks(8)
+        .map(|chunk| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(chunk);
+            u64::from_be_bytes(buf)
+        })
+        .collect();
+    values
+        .chunks(3)
+        .map(|range| SyncedRange::new(range[0], range[1], range[2]))
+        .collect()
+}
+
+/// replace `[start, end)` of `ranges` with `synced_range`, keeping them sorted
+fn merge(ranges: &[SyncedRange], synced_range: &SyncedRange) -> Vec<SyncedRange> {
+    let (start, end) = (synced_range.get_start(), synced_range.get_end());
+    let mut merged = vec![synced_range.clone()];
+    for range in ranges.iter() {
+        if range.get_end() <= start || end <= range.get_start() {
+            merged.push(range.clone());
+            continue;
+        }
+        if range.get_start() < start {
+            merged.push(SyncedRange::new(
+                range.get_start(),
+                start,
+                range.get_block_number(),
+            ));
+        }
+        if end < range.get_end() {
+            merged.push(SyncedRange::new(
+                end,
+                range.get_end(),
+                range.get_block_number(),
+            ));
+        }
+    }
+    merged.sort_by_key(SyncedRange::get_start);
+    merged
+}
+
+/// SyncDb records the verified block number per range, and headers of verified blocks.
+/// Watched ranges are few, so they are cached in memory and written as a whole.
+pub struct SyncDb {
+    db: RwLock<Box<dyn KeyValueStore + Send + Sync>>,
+    ranges: RwLock<Vec<SyncedRange>>,
+}
+
+impl Default for SyncDb {
+    fn default() -> Self {
+        Self::new(Box::new(CoreDb::open("sync"))).expect("empty db can't fail")
+    }
+}
+
+impl SyncDb {
+    const RANGES_KEY: &'static [u8] = b"ranges";
+    const HEADER_PREFIX: &'static [u8] = b"header";
+
+    /// open a db, which may have ranges and headers written before restart
+    pub fn new(db: Box<dyn KeyValueStore + Send + Sync>) -> Result<Self, Error> {
+        let ranges = db
+            .get(Self::RANGES_KEY)?
+            .map_or_else(Vec::new, |bytes| decode_ranges(&bytes));
+        Ok(SyncDb {
+            db: RwLock::new(db),
+            ranges: RwLock::new(ranges),
+        })
+    }
+    fn header_key(block_number: u64) -> Vec<u8> {
+        let mut key = Self::HEADER_PREFIX.to_vec();
+        key.extend_from_slice(&block_number.to_be_bytes());
+        key
+    }
+    pub fn put_synced_range(&self, synced_range: &SyncedRange) -> Result<(), Error> {
+        let mut db = self.db.write();
+        let mut ranges = self.ranges.write();
+        let merged = merge(&ranges, synced_range);
+        db.put(Self::RANGES_KEY, &encode_ranges(&merged))?;
+        *ranges = merged;
+        Ok(())
+    }
+    /// mark every watched range verified until the block of `header`, and keep the header.
+    /// Both are written at once, so that they never disagree after a crash.
+    pub fn put_synced_block(&self, header: &BlockHeader) -> Result<(), Error> {
+        let db = self.db.write();
+        let mut ranges = self.ranges.write();
+        let block_number = header.get_number();
+        let synced: Vec<SyncedRange> = ranges
+            .iter()
+            .map(|range| {
+                SyncedRange::new(
+                    range.get_start(),
+                    range.get_end(),
+                    max(range.get_block_number(), block_number),
+                )
+            })
+            .collect();
+        let encoded_ranges = encode_ranges(&synced);
+        let encoded_header = rlp::encode(header);
+        db.batch(&[
+            Batch::BatchPut {
+                key: Self::RANGES_KEY,
+                value: &encoded_ranges,
+            },
+            Batch::BatchPut {
+                key: &Self::header_key(block_number),
+                value: &encoded_header,
+            },
+        ])?;
+        *ranges = synced;
+        Ok(())
+    }
+    /// the header of a verified block
+    pub fn get_header(&self, block_number: u64) -> Result<Option<BlockHeader>, Error> {
+        match self.db.read().get(&Self::header_key(block_number))? {
+            Some(encoded) => Ok(Some(rlp::decode(&encoded)?)),
+            None => Ok(None),
+        }
+    }
+    /// watched ranges between start and end
+    pub fn get_synced_ranges(&self, start: u64, end: u64) -> Result<Vec<SyncedRange>, Error> {
+        Ok(self
+            .ranges
+            .read()
+            .iter()
+            .filter(|range| range.get_start() < end && start < range.get_end())
+            .map(|range| {
+                SyncedRange::new(
+                    max(range.get_start(), start),
+                    min(range.get_end(), end),
+                    range.get_block_number(),
+                )
+            })
+            .collect())
+    }
+    /// every watched range
+    pub fn get_all_synced_ranges(&self) -> Result<Vec<SyncedRange>, Error> {
+        self.get_synced_ranges(0, std::u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SyncDb, SyncedRange};
+    use ethereum_types::H256;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::data_structure::BlockHeader;
+    use plasma_db::impls::kvs::leveldb::KvsLevelImpl;
+    use tempdir::TempDir;
+
+    fn open(dir: &TempDir) -> SyncDb {
+        SyncDb::new(Box::new(KvsLevelImpl::open_path(dir.path()).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn test_put_synced_range() {
+        let db: SyncDb = Default::default();
+        assert!(db.put_synced_range(&SyncedRange::new(0, 100, 1)).is_ok());
+        assert!(db.put_synced_range(&SyncedRange::new(50, 150, 2)).is_ok());
+        assert_eq!(
+            db.get_all_synced_ranges().unwrap(),
+            vec![SyncedRange::new(0, 50, 1), SyncedRange::new(50, 150, 2)]
+        );
+        assert_eq!(
+            db.get_synced_ranges(20, 60).unwrap(),
+            vec![SyncedRange::new(20, 50, 1), SyncedRange::new(50, 60, 2)]
+        );
+    }
+
+    #[test]
+    fn test_reopen() {
+        let dir = TempDir::new("sync").unwrap();
+        let header = BlockHeader::sign(
+            3,
+            &H256::zero(),
+            &H256::zero(),
+            0,
+            &SecretKey::parse(&[1u8; 32]).unwrap(),
+        )
+        .unwrap();
+        {
+            let db = open(&dir);
+            assert!(db.put_synced_range(&SyncedRange::new(0, 100, 1)).is_ok());
+            assert!(db.put_synced_range(&SyncedRange::new(100, 200, 5)).is_ok());
+            assert!(db.put_synced_block(&header).is_ok());
+        }
+        let db = open(&dir);
+        assert_eq!(
+            db.get_all_synced_ranges().unwrap(),
+            vec![SyncedRange::new(0, 100, 3), SyncedRange::new(100, 200, 5)]
+        );
+        assert_eq!(db.get_header(3).unwrap(), Some(header));
+        assert_eq!(db.get_header(2).unwrap(), None);
+    }
+}
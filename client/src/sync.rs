@@ -0,0 +1,264 @@
+//
+// Created on Tue Jun 25 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use crate::error::{Error, ErrorKind};
+use crate::state::{StateManager, SyncDb, SyncedRange, VerifiedStateUpdate};
+use ethereum_types::Address;
+use futures::Future;
+use log::{error, info, warn};
+use plasma_core::data_structure::Block;
+use plasma_core::process::HeaderVerifier;
+use plasma_rpc::client::OperatorClient;
+use std::thread;
+use std::time::Duration;
+
+/// SyncEngine pulls new blocks from the operator, and applies transactions
+/// touching watched ranges after verifying their inclusion proofs.
+pub struct SyncEngine {
+    state_manager: StateManager,
+    sync_db: SyncDb,
+    operator: OperatorClient,
+    operator_address: Address,
+}
+
+impl SyncEngine {
+    pub fn new(
+        state_manager: StateManager,
+        sync_db: SyncDb,
+        operator: OperatorClient,
+        operator_address: Address,
+    ) -> Self {
+        SyncEngine {
+            state_manager,
+            sync_db,
+            operator,
+            operator_address,
+        }
+    }
+    pub fn get_state_manager(&self) -> &StateManager {
+        &self.state_manager
+    }
+    pub fn get_sync_db(&self) -> &SyncDb {
+        &self.sync_db
+    }
+
+    /// Start watching a range which is verified until `block_number`, such as a deposit.
+    pub fn watch(&self, start: u64, end: u64, block_number: u64) -> Result<(), Error> {
+        self.sync_db
+            .put_synced_range(&SyncedRange::new(start, end, block_number))
+    }
+
+    /// Verify a block and apply its transactions touching watched ranges.
+    /// The block has to follow the header of the previous block if it was verified.
+    /// Invalid state transitions are recorded as fraud proofs and skipped.
+    pub fn apply_block(&self, block: &Block) -> Result<Vec<VerifiedStateUpdate>, Error> {
+        let block_number = block.get_number();
+        let tree = Block::compute_tree(block.get_transactions());
+        HeaderVerifier::verify_block_tree(block, tree.as_ref(), &self.operator_address)?;
+        if let Some(prev) = self.sync_db.get_header(block_number.saturating_sub(1))? {
+            HeaderVerifier::verify_next(&prev, block.get_header(), &self.operator_address)?;
+        }
+        let mut verified_state_updates = vec![];
+        for (index, transaction) in block.get_transactions().iter().enumerate() {
+            let is_watched = self
+                .sync_db
+                .get_synced_ranges(transaction.get_start(), transaction.get_end())?
+                .iter()
+                .any(|synced_range| synced_range.get_block_number() < block_number);
+            if !is_watched {
+                continue;
+            }
+            let inclusion_proof = tree
+                .as_ref()
+                .and_then(|tree| block.get_inclusion_proof_in(tree, index))
+                .expect("index is less than the number of transactions");
+            match self.state_manager.apply_included_transaction(
+                block_number,
+                block.get_root(),
+                transaction,
+                &inclusion_proof,
+            ) {
+                Ok(applied) => verified_state_updates.extend_from_slice(&applied),
+                Err(ref err) if is_invalid_state_transition(err) => warn!(
+                    "invalid state transition in block {}, fraud proof is recorded",
+                    block_number
+                ),
+                Err(err) => return Err(err),
+            }
+        }
+        self.sync_db.put_synced_block(block.get_header())?;
+        Ok(verified_state_updates)
+    }
+
+    /// Fetch and apply blocks which were generated since the last poll.
+    /// Returns the block number which every watched range is verified until.
+    pub fn poll(&self) -> Result<Option<u64>, Error> {
+        let synced_block_number = match self
+            .sync_db
+            .get_all_synced_ranges()?
+            .iter()
+            .map(SyncedRange::get_block_number)
+            .min()
+        {
+            Some(block_number) => block_number,
+            None => return Ok(None),
+        };
+        let latest_block_number = self.operator.get_block_number().wait()?;
+        for block_number in synced_block_number + 1..=latest_block_number {
+            let block = self.operator.get_block(block_number).wait()?;
+            self.apply_block(&block)?;
+            info!("verified block {}", block_number);
+        }
+        Ok(Some(latest_block_number.max(synced_block_number)))
+    }
+
+    /// Keep polling blocks forever.
+    pub fn run(&self, interval: Duration) {
+        loop {
+            if let Err(err) = self.poll() {
+                error!("failed to sync blocks: {}", err);
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+fn is_invalid_state_transition(err: &Error) -> bool {
+    match err.kind() {
+        ErrorKind::InvalidStateTransition => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SyncEngine;
+    use crate::test_utils::{create_state_update, create_transaction};
+    use ethereum_types::H256;
+    use futures::Future;
+    use jsonrpc_core::IoHandler;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::process::BlockGenerator;
+    use plasma_operator::context::ChainContext;
+    use plasma_operator::rpc::plasmarpcimpl::PlasmaRpcImpl;
+    use plasma_rpc::client::OperatorClient;
+    use plasma_rpc::plasmarpc::PlasmaRpc;
+    use std::thread;
+
+    fn create_sync_engine(chain_context: &ChainContext) -> SyncEngine {
+        let mut io = IoHandler::new();
+        io.extend_with(PlasmaRpcImpl::from_chain_context(chain_context.clone()).to_delegate());
+        let (operator, rpc_client) = OperatorClient::connect_local(io);
+        thread::spawn(move || rpc_client.wait());
+        SyncEngine::new(
+            Default::default(),
+            Default::default(),
+            operator,
+            chain_context.get_operator_address(),
+        )
+    }
+
+    #[test]
+    fn test_poll() {
+        let chain_context = ChainContext::new(&SecretKey::parse(&[1u8; 32]).unwrap());
+        let deposit = create_state_update(0, 200, 0);
+        assert!(chain_context.deposit(&deposit).is_ok());
+        let sync_engine = create_sync_engine(&chain_context);
+        assert_eq!(sync_engine.poll().unwrap(), None);
+        assert!(sync_engine
+            .get_state_manager()
+            .deposit(0, 100, &deposit)
+            .is_ok());
+        assert!(sync_engine.watch(0, 100, 0).is_ok());
+
+        assert!(chain_context.append(&create_transaction(0, 50)).is_ok());
+        assert!(chain_context.append(&create_transaction(100, 200)).is_ok());
+        assert!(chain_context.generate().is_ok());
+        assert!(chain_context.generate().is_ok());
+
+        assert_eq!(sync_engine.poll().unwrap(), Some(2));
+        let synced_ranges = sync_engine.get_sync_db().get_all_synced_ranges().unwrap();
+        assert_eq!(synced_ranges.len(), 1);
+        assert_eq!(synced_ranges[0].get_block_number(), 2);
+        let verified_state_updates = sync_engine
+            .get_state_manager()
+            .get_verified_state_updates(0, 50)
+            .unwrap();
+        assert_eq!(
+            verified_state_updates[0]
+                .get_state_update()
+                .get_block_number(),
+            1
+        );
+        assert!(verified_state_updates[0].get_inclusion_proof().is_some());
+        assert!(sync_engine
+            .get_state_manager()
+            .get_verified_state_updates(100, 200)
+            .unwrap()
+            .is_empty());
+        assert_eq!(sync_engine.poll().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_reject_block_of_another_operator() {
+        let chain_context: ChainContext = Default::default();
+        let sync_engine = create_sync_engine(&chain_context);
+        let deposit = create_state_update(0, 100, 0);
+        assert!(sync_engine
+            .get_state_manager()
+            .deposit(0, 100, &deposit)
+            .is_ok());
+        assert!(sync_engine.watch(0, 100, 0).is_ok());
+
+        let another_key = SecretKey::parse(&[2u8; 32]).unwrap();
+        let block = BlockGenerator::generate(
+            &[create_transaction(0, 50)],
+            1,
+            &H256::zero(),
+            0,
+            &another_key,
+        )
+        .unwrap();
+        assert!(sync_engine.apply_block(&block).is_err());
+        assert_eq!(
+            sync_engine.get_sync_db().get_all_synced_ranges().unwrap()[0].get_block_number(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_reject_block_not_following_synced_header() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let chain_context = ChainContext::new(&operator_key);
+        let deposit = create_state_update(0, 100, 0);
+        assert!(chain_context.deposit(&deposit).is_ok());
+        let sync_engine = create_sync_engine(&chain_context);
+        assert!(sync_engine
+            .get_state_manager()
+            .deposit(0, 100, &deposit)
+            .is_ok());
+        assert!(sync_engine.watch(0, 100, 0).is_ok());
+        assert!(chain_context.append(&create_transaction(0, 50)).is_ok());
+        assert!(chain_context.generate().is_ok());
+        assert_eq!(sync_engine.poll().unwrap(), Some(1));
+
+        // signed by the operator, but doesn't refer to block 1
+        let block = BlockGenerator::generate(
+            &[create_transaction(50, 100)],
+            2,
+            &H256::zero(),
+            0,
+            &operator_key,
+        )
+        .unwrap();
+        assert!(sync_engine.apply_block(&block).is_err());
+        assert_eq!(
+            sync_engine.get_sync_db().get_all_synced_ranges().unwrap()[0].get_block_number(),
+            1
+        );
+    }
+}
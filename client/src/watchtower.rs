@@ -0,0 +1,240 @@
+//
+// Created on Mon Jun 10 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use crate::contract::memory::MemoryPlasmaContract;
+use crate::contract::{Challenge, Exit, PlasmaContract};
+use crate::error::Error;
+use crate::state::StateDb;
+use log::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Watchtower follows exits on the plasma contract and challenges invalid ones
+/// on behalf of users who are offline.
+pub struct Watchtower<C: PlasmaContract> {
+    db: StateDb,
+    contract: C,
+    next_exit_id: u64,
+}
+
+impl<C: PlasmaContract> Watchtower<C> {
+    pub fn new(db: StateDb, contract: C) -> Self {
+        Watchtower {
+            db,
+            contract,
+            next_exit_id: 0,
+        }
+    }
+    pub fn get_contract(&self) -> &C {
+        &self.contract
+    }
+
+    /// Check an exit against locally verified history.
+    /// Returns a challenge if the exited state update was deprecated by a later one.
+    /// A later state update verified without its transaction and inclusion proof can't be a challenge.
+    pub fn check_exit(&self, exit: &Exit) -> Result<Option<Challenge>, Error> {
+        let exited = exit.get_state_update();
+        let verified_state_updates = self
+            .db
+            .get_verified_state_updates(exited.get_start(), exited.get_end())?;
+        Ok(verified_state_updates
+            .iter()
+            .filter(|verified_state_update| {
+                verified_state_update.get_state_update().get_block_number()
+                    > exited.get_block_number()
+            })
+            .find_map(|verified_state_update| {
+                match (
+                    verified_state_update.get_transaction(),
+                    verified_state_update.get_inclusion_proof(),
+                ) {
+                    (Some(transaction), Some(inclusion_proof)) => Some(Challenge::new(
+                        exit.get_id(),
+                        verified_state_update.get_state_update(),
+                        transaction,
+                        inclusion_proof,
+                    )),
+                    _ => None,
+                }
+            }))
+    }
+
+    /// Process exits which were started since the last poll, and challenge invalid ones.
+    pub fn poll(&mut self) -> Result<Vec<Challenge>, Error> {
+        let exits = self.contract.get_exits(self.next_exit_id)?;
+        let mut challenges = vec![];
+        for exit in exits.iter() {
+            if let Some(challenge) = self.check_exit(exit)? {
+                self.contract.challenge_exit(&challenge)?;
+                info!("challenged exit {}", exit.get_id());
+                challenges.push(challenge);
+            }
+            self.next_exit_id = exit.get_id() + 1;
+        }
+        Ok(challenges)
+    }
+
+    /// Keep polling exits while `running` is set.
+    pub fn run(&mut self, interval: Duration, running: &AtomicBool) {
+        while running.load(Ordering::SeqCst) {
+            if let Err(err) = self.poll() {
+                error!("failed to process exits: {}", err);
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// entry point of watchtower.
+/// It follows the in-memory plasma contract until a binding for L1 is available.
+pub fn entry(interval: Duration) {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let mut watchtower = Watchtower::new(StateDb::default(), MemoryPlasmaContract::default());
+    info!(
+        "watchtower polls exits every {} seconds",
+        interval.as_secs()
+    );
+    watchtower.run(interval, &AtomicBool::new(true));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Watchtower;
+    use crate::contract::memory::MemoryPlasmaContract;
+    use crate::state::{StateDb, VerifiedStateUpdate};
+    use crate::test_utils::{create_state_update, create_transaction};
+    use ethereum_types::H256;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::process::BlockGenerator;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// a state update verified by the inclusion proof of its transaction
+    fn create_verified_state_update(
+        start: u64,
+        end: u64,
+        block_number: u64,
+    ) -> VerifiedStateUpdate {
+        let transaction = create_transaction(start, end);
+        let block = BlockGenerator::generate(
+            &[transaction.clone()],
+            block_number,
+            &H256::zero(),
+            0,
+            &SecretKey::parse(&[1u8; 32]).unwrap(),
+        )
+        .unwrap();
+        VerifiedStateUpdate::from_included(
+            start,
+            end,
+            block_number,
+            &create_state_update(start, end, block_number),
+            &transaction,
+            &block.get_inclusion_proof(0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_challenge_invalid_exit() {
+        let db: StateDb = Default::default();
+        let spent = create_state_update(0, 100, 1);
+        let latest = create_verified_state_update(0, 100, 5);
+        assert!(db.put_verified_state_update(&latest).is_ok());
+        let mut watchtower = Watchtower::new(db, MemoryPlasmaContract::default());
+        let exit_id = watchtower.get_contract().start_exit(&spent);
+
+        let challenges = watchtower.poll().unwrap();
+        assert_eq!(challenges.len(), 1);
+        assert_eq!(challenges[0].get_exit_id(), exit_id);
+        assert_eq!(challenges[0].get_deprecation(), latest.get_state_update());
+        assert_eq!(
+            Some(challenges[0].get_transaction()),
+            latest.get_transaction()
+        );
+        assert_eq!(
+            Some(challenges[0].get_inclusion_proof()),
+            latest.get_inclusion_proof()
+        );
+        assert_eq!(watchtower.get_contract().get_challenges(), challenges);
+    }
+
+    #[test]
+    fn test_do_not_challenge_valid_exit() {
+        let db: StateDb = Default::default();
+        let latest = create_verified_state_update(0, 100, 5);
+        assert!(db.put_verified_state_update(&latest).is_ok());
+        let mut watchtower = Watchtower::new(db, MemoryPlasmaContract::default());
+        watchtower
+            .get_contract()
+            .start_exit(latest.get_state_update());
+
+        assert!(watchtower.poll().unwrap().is_empty());
+        assert!(watchtower.get_contract().get_challenges().is_empty());
+    }
+
+    #[test]
+    fn test_do_not_challenge_without_transaction() {
+        let db: StateDb = Default::default();
+        let latest = create_state_update(0, 100, 5);
+        assert!(db
+            .put_verified_state_update(&VerifiedStateUpdate::from(5, &latest))
+            .is_ok());
+        let mut watchtower = Watchtower::new(db, MemoryPlasmaContract::default());
+        watchtower
+            .get_contract()
+            .start_exit(&create_state_update(0, 100, 1));
+
+        assert!(watchtower.poll().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_process_each_exit_once() {
+        let db: StateDb = Default::default();
+        let latest = create_verified_state_update(0, 100, 5);
+        assert!(db.put_verified_state_update(&latest).is_ok());
+        let mut watchtower = Watchtower::new(db, MemoryPlasmaContract::default());
+        watchtower
+            .get_contract()
+            .start_exit(&create_state_update(0, 50, 2));
+
+        assert_eq!(watchtower.poll().unwrap().len(), 1);
+        assert!(watchtower.poll().unwrap().is_empty());
+        assert_eq!(watchtower.get_contract().get_challenges().len(), 1);
+    }
+
+    #[test]
+    fn test_run_until_stopped() {
+        let contract = Arc::new(MemoryPlasmaContract::default());
+        let running = Arc::new(AtomicBool::new(true));
+        let handle = {
+            let contract = contract.clone();
+            let running = running.clone();
+            thread::spawn(move || {
+                let db: StateDb = Default::default();
+                db.put_verified_state_update(&create_verified_state_update(0, 100, 5))
+                    .unwrap();
+                Watchtower::new(db, contract).run(Duration::from_millis(10), &running)
+            })
+        };
+
+        // an exit started while the watchtower is running
+        let exit_id = contract.start_exit(&create_state_update(0, 100, 1));
+        let started = Instant::now();
+        while contract.get_challenges().is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(10));
+        }
+        running.store(false, Ordering::SeqCst);
+        handle.join().unwrap();
+        let challenges = contract.get_challenges();
+        assert_eq!(challenges.len(), 1);
+        assert_eq!(challenges[0].get_exit_id(), exit_id);
+    }
+}
@@ -1,10 +1,20 @@
 pub mod block;
+pub mod block_header;
 pub mod error;
+pub mod inclusion_proof;
+pub mod inclusion_receipt;
+pub mod signature;
+pub mod signed_root;
 pub mod state_object;
 pub mod state_update;
 pub mod transaction;
 
 pub use self::block::Block;
+pub use self::block_header::BlockHeader;
+pub use self::inclusion_proof::InclusionProof;
+pub use self::inclusion_receipt::InclusionReceipt;
+pub use self::signature::Signature;
+pub use self::signed_root::SignedRoot;
 pub use self::state_object::StateObject;
 pub use self::state_update::StateUpdate;
 pub use self::transaction::Transaction;
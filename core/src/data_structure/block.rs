@@ -1,31 +1,137 @@
 extern crate ethereum_types;
 
+use super::block_header::BlockHeader;
+use super::inclusion_proof::InclusionProof;
 use super::transaction::Transaction;
+use bytes::Bytes;
 use ethereum_types::H256;
 use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use sum_merkle_tree::{SumMerkleNode, SumMerkleTree};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 /// ## struct Block
+/// - has a `header` signed by operator
 /// - has many `transactions`
-/// - has a `merkle root hash`
 /// - Traits
 ///   - Encodable
 ///   - Decodable
 pub struct Block {
+    header: BlockHeader,
     transactions: Vec<Transaction>,
-    root: H256,
 }
 
 impl Block {
     /// ### Block.new
     /// A constructor of a Block struct
     /// ```ignore
-    /// let block = Block.new(&txs, root)
+    /// let block = Block.new(&header, &txs)
     /// ```
-    pub fn new(transactions: &[Transaction], root: H256) -> Block {
+    pub fn new(header: &BlockHeader, transactions: &[Transaction]) -> Block {
         Block {
+            header: header.clone(),
             transactions: transactions.to_vec(),
-            root,
+        }
+    }
+    /// ### Block.create_leaf
+    /// A static function to make a leaf of sum merkle tree from a transaction
+    /// ```ignore
+    /// let leaf = Block.create_leaf(&tx)
+    /// ```
+    pub fn create_leaf(transaction: &Transaction) -> SumMerkleNode {
+        SumMerkleNode::create_leaf(transaction.get_end(), Bytes::from(transaction.to_abi()))
+    }
+    /// ### Block.compute_tree
+    /// A static function to build sum merkle tree of transactions
+    /// ```ignore
+    /// let tree = Block.compute_tree(&txs)
+    /// ```
+    pub fn compute_tree(transactions: &[Transaction]) -> Option<SumMerkleTree> {
+        if transactions.is_empty() {
+            return None;
+        }
+        let leaves: Vec<SumMerkleNode> = transactions.iter().map(Block::create_leaf).collect();
+        Some(SumMerkleTree::generate(&leaves))
+    }
+    /// ### Block.compute_root
+    /// A static function to calculate merkle root of transactions
+    /// ```ignore
+    /// let root = Block.compute_root(&txs)
+    /// ```
+    pub fn compute_root(transactions: &[Transaction]) -> H256 {
+        Block::get_root_of(Block::compute_tree(transactions).as_ref())
+    }
+    /// ### Block.get_root_of
+    /// A static function to get merkle root of a tree built by `compute_tree`
+    /// ```ignore
+    /// let root = Block.get_root_of(tree.as_ref())
+    /// ```
+    pub fn get_root_of(tree: Option<&SumMerkleTree>) -> H256 {
+        tree.map(|tree| H256::from_slice(&tree.get_root()))
+            .unwrap_or_else(H256::zero)
+    }
+    pub fn get_header(&self) -> &BlockHeader {
+        &self.header
+    }
+    pub fn get_number(&self) -> u64 {
+        self.header.get_number()
+    }
+    pub fn get_root(&self) -> &H256 {
+        self.header.get_root()
+    }
+    /// ### block.hash()
+    /// A function to get the block hash
+    pub fn hash(&self) -> H256 {
+        self.header.hash()
+    }
+    pub fn get_transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+    /// ### block.get_inclusion_proof()
+    /// A function to get inclusion proof of a transaction at `index`
+    /// ```ignore
+    /// let proof = block.get_inclusion_proof(0)
+    /// ```
+    pub fn get_inclusion_proof(&self, index: usize) -> Option<InclusionProof> {
+        Block::compute_tree(&self.transactions)
+            .and_then(|tree| self.get_inclusion_proof_in(&tree, index))
+    }
+    /// ### block.get_inclusion_proof_in()
+    /// A function to get inclusion proof of a transaction at `index` from `tree`
+    /// built by `compute_tree`, so that the tree is built once for many proofs
+    /// ```ignore
+    /// let tree = Block.compute_tree(block.get_transactions()).unwrap()
+    /// let proof = block.get_inclusion_proof_in(&tree, 0)
+    /// ```
+    pub fn get_inclusion_proof_in(
+        &self,
+        tree: &SumMerkleTree,
+        index: usize,
+    ) -> Option<InclusionProof> {
+        if index >= self.transactions.len() {
+            return None;
+        }
+        Some(InclusionProof::new(
+            index as u64,
+            &tree.get_inclusion_proof(index, self.transactions.len()),
+        ))
+    }
+    /// ### block.get_exclusion_proof()
+    /// A function to get exclusion proof of a range `[start, end)`, which is the inclusion proof
+    /// of the transaction whose leaf covers the range without touching it.
+    /// Returns `None` if the range is touched in the block or spans more than one leaf.
+    /// An empty block has no proof because it touches nothing.
+    /// ```ignore
+    /// let proof = block.get_exclusion_proof(0, 100)
+    /// let transaction = &block.get_transactions()[proof.get_leaf_index() as usize]
+    /// ```
+    pub fn get_exclusion_proof(&self, start: u64, end: u64) -> Option<InclusionProof> {
+        let tree = Block::compute_tree(&self.transactions)?;
+        let (index, nodes) = tree.get_exclusion_proof(start, end)?;
+        let transaction = &self.transactions[index];
+        if transaction.get_start() < end && start < transaction.get_end() {
+            None
+        } else {
+            Some(InclusionProof::new(index as u64, &nodes))
         }
     }
 }
@@ -33,23 +139,26 @@ impl Block {
 impl Encodable for Block {
     fn rlp_append(&self, s: &mut RlpStream) {
         s.begin_list(2);
+        s.append(&self.header);
         s.append_list(&self.transactions);
-        s.append(&self.root.as_bytes());
     }
 }
 
 impl Decodable for Block {
     fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
-        let transactions: Vec<Transaction> = rlp.list_at(0)?;
-        let root: Vec<u8> = rlp.val_at(1)?;
-        Ok(Block::new(&transactions, H256::from_slice(&root)))
+        let header: BlockHeader = rlp.val_at(0)?;
+        let transactions: Vec<Transaction> = rlp.list_at(1)?;
+        Ok(Block::new(&header, &transactions))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Block;
-    use ethereum_types::H256;
+    use crate::data_structure::signature::SecretKey;
+    use crate::data_structure::{BlockHeader, Transaction, Witness};
+    use crate::process::BlockGenerator;
+    use ethereum_types::{Address, H256};
 
     /*
         #[test]
@@ -61,10 +170,34 @@ mod tests {
 
     #[test]
     fn test_rlp_encode() {
-        let block = Block::new(&[], H256::zero());
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let header = BlockHeader::sign(1, &H256::zero(), &H256::zero(), 0, &operator_key).unwrap();
+        let block = Block::new(&header, &[]);
         let encoded = rlp::encode(&block);
         let _decoded: Block = rlp::decode(&encoded).unwrap();
-        assert_eq!(_decoded.root, block.root);
+        assert_eq!(_decoded.get_root(), block.get_root());
     }
 
+    #[test]
+    fn test_inclusion_proof() {
+        let transactions: Vec<Transaction> = (0..5)
+            .map(|i| {
+                Transaction::new(
+                    Address::zero(),
+                    i * 100,
+                    (i + 1) * 100,
+                    Transaction::create_method_id(&b"send(address)"[..]),
+                    &b"parameters"[..],
+                    &Witness::new(H256::zero(), H256::zero(), 0),
+                )
+            })
+            .collect();
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let block =
+            BlockGenerator::generate(&transactions, 1, &H256::zero(), 0, &operator_key).unwrap();
+        let proof = block.get_inclusion_proof(3).unwrap();
+        let leaf = Block::create_leaf(&block.get_transactions()[3]);
+        assert!(proof.verify(&leaf, block.get_root()));
+        assert!(block.get_inclusion_proof(5).is_none());
+    }
 }
@@ -0,0 +1,156 @@
+extern crate ethabi;
+extern crate ethereum_types;
+extern crate tiny_keccak;
+
+use super::error::{Error, ErrorKind};
+use super::signature::{SecretKey, Signature};
+use ethabi::Token;
+use ethereum_types::{Address, H256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use tiny_keccak::keccak256;
+
+#[derive(Clone, Debug, PartialEq)]
+/// ## struct BlockHeader
+/// - has a block `number`
+/// - has a `merkle root hash` of transactions
+/// - has a `prev_hash` (hash of the previous block header)
+/// - has a `timestamp`
+/// - has an `operator_signature` over the block hash
+/// - Traits
+///   - Encodable
+///   - Decodable
+pub struct BlockHeader {
+    number: u64,
+    root: H256,
+    prev_hash: H256,
+    timestamp: u64,
+    operator_signature: Signature,
+}
+
+impl BlockHeader {
+    /// ### BlockHeader.sign
+    /// A constructor of a BlockHeader struct signed by operator
+    /// ```ignore
+    /// let header = BlockHeader.sign(number, &root, &prev_hash, timestamp, &operator_key)
+    /// ```
+    pub fn sign(
+        number: u64,
+        root: &H256,
+        prev_hash: &H256,
+        timestamp: u64,
+        operator_key: &SecretKey,
+    ) -> Result<Self, Error> {
+        let hash = Self::compute_hash(number, root, prev_hash, timestamp);
+        Ok(BlockHeader {
+            number,
+            root: *root,
+            prev_hash: *prev_hash,
+            timestamp,
+            operator_signature: Signature::sign(&hash, operator_key)?,
+        })
+    }
+    fn compute_hash(number: u64, root: &H256, prev_hash: &H256, timestamp: u64) -> H256 {
+        H256::from(keccak256(&ethabi::encode(&[
+            Token::Uint(number.into()),
+            Token::FixedBytes(root.as_bytes().to_vec()),
+            Token::FixedBytes(prev_hash.as_bytes().to_vec()),
+            Token::Uint(timestamp.into()),
+        ])))
+    }
+    /// ### header.hash()
+    /// A function to get the block hash, which the operator signs
+    /// ```ignore
+    /// let hash = header.hash()
+    /// ```
+    pub fn hash(&self) -> H256 {
+        Self::compute_hash(self.number, &self.root, &self.prev_hash, self.timestamp)
+    }
+    pub fn get_number(&self) -> u64 {
+        self.number
+    }
+    pub fn get_root(&self) -> &H256 {
+        &self.root
+    }
+    pub fn get_prev_hash(&self) -> &H256 {
+        &self.prev_hash
+    }
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+    pub fn get_operator_signature(&self) -> &Signature {
+        &self.operator_signature
+    }
+    /// ### header.recover_operator()
+    /// A function to recover the address which signed the header
+    pub fn recover_operator(&self) -> Result<Address, Error> {
+        self.operator_signature.recover(&self.hash())
+    }
+    /// ### header.verify()
+    /// A function to check whether `operator` signed the header
+    pub fn verify(&self, operator: &Address) -> Result<(), Error> {
+        if &self.recover_operator()? == operator {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::InvalidSignature))
+        }
+    }
+}
+
+impl Encodable for BlockHeader {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(5);
+        s.append(&self.number);
+        s.append(&self.root.as_bytes());
+        s.append(&self.prev_hash.as_bytes());
+        s.append(&self.timestamp);
+        s.append(&self.operator_signature.to_bytes());
+    }
+}
+
+impl Decodable for BlockHeader {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let number: u64 = rlp.val_at(0)?;
+        let root: Vec<u8> = rlp.val_at(1)?;
+        let prev_hash: Vec<u8> = rlp.val_at(2)?;
+        let timestamp: u64 = rlp.val_at(3)?;
+        let operator_signature: Vec<u8> = rlp.val_at(4)?;
+        if root.len() != 32 || prev_hash.len() != 32 {
+            return Err(DecoderError::RlpInvalidLength);
+        }
+        Ok(BlockHeader {
+            number,
+            root: H256::from_slice(&root),
+            prev_hash: H256::from_slice(&prev_hash),
+            timestamp,
+            operator_signature: Signature::from_bytes(&operator_signature)
+                .map_err(|_e| DecoderError::Custom("invalid signature"))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockHeader;
+    use crate::data_structure::signature::{SecretKey, Signature};
+    use ethereum_types::{Address, H256};
+
+    #[test]
+    fn test_verify() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let header =
+            BlockHeader::sign(1, &H256::repeat_byte(1), &H256::zero(), 0, &operator_key).unwrap();
+        assert!(header.verify(&Signature::address_of(&operator_key)).is_ok());
+        assert!(header.verify(&Address::zero()).is_err());
+    }
+
+    #[test]
+    fn test_rlp_encode() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let header =
+            BlockHeader::sign(1, &H256::repeat_byte(1), &H256::zero(), 0, &operator_key).unwrap();
+        let encoded = rlp::encode(&header);
+        let decoded: BlockHeader = rlp::decode(&encoded).unwrap();
+        assert_eq!(decoded, header);
+        assert_eq!(decoded.hash(), header.hash());
+    }
+}
@@ -18,6 +18,18 @@ pub enum ErrorKind {
     Io,
     #[fail(display = "ABI Decode error")]
     AbiDecode,
+    #[fail(display = "JSON Decode error")]
+    JsonDecode,
+    #[fail(display = "Invalid inclusion proof")]
+    InvalidInclusionProof,
+    #[fail(display = "Invalid exclusion proof")]
+    InvalidExclusionProof,
+    #[fail(display = "Invalid state transition")]
+    InvalidStateTransition,
+    #[fail(display = "Invalid signature")]
+    InvalidSignature,
+    #[fail(display = "Invalid block header")]
+    InvalidBlockHeader,
 }
 
 #[derive(Debug)]
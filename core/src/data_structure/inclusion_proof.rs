@@ -0,0 +1,368 @@
+extern crate ethabi;
+
+use super::error::{Error, ErrorKind};
+use bytes::Bytes;
+use ethabi::Token;
+use ethereum_types::{H256, U256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use serde_derive::{Deserialize, Serialize};
+use sum_merkle_tree::{ImplicitBounds, Keccak256, SumMerkleNode, SumMerkleTree};
+
+#[derive(Clone, Debug, PartialEq)]
+/// ## struct InclusionProof
+/// - has a `leaf_index` of the transaction in a block
+/// - has many `nodes` (sibling nodes from the leaf to the root)
+///
+/// Every node is serialized as a pair of the sibling hash and its end, ordered from the leaf to the root.
+/// - ABI: `(uint256 leafIndex, uint256[] ends, bytes32[] hashes)`
+/// - RLP: `[leafIndex, [[end, hash], ...]]`
+/// - JSON: `InclusionProofJson`
+///
+/// The ABI layout is intended as the input of `verifyInclusion` of the plasma contract.
+/// A parent hash is `keccak256(keccak256(left.end, left.hash), keccak256(right.end, right.hash))`
+/// with big-endian `uint64` ends, as packed by the contract.
+/// The contract and the JS library are not in this repository, so it is not cross-checked with them yet.
+pub struct InclusionProof {
+    leaf_index: u64,
+    nodes: Vec<SumMerkleNode>,
+}
+
+impl InclusionProof {
+    pub fn new(leaf_index: u64, nodes: &[SumMerkleNode]) -> Self {
+        InclusionProof {
+            leaf_index,
+            nodes: nodes.to_vec(),
+        }
+    }
+    pub fn get_leaf_index(&self) -> u64 {
+        self.leaf_index
+    }
+    pub fn get_nodes(&self) -> &[SumMerkleNode] {
+        &self.nodes
+    }
+    /// pairs of the sibling hash and its end from the leaf to the root
+    pub fn get_proof_nodes(&self) -> Vec<(H256, u64)> {
+        self.nodes
+            .iter()
+            .map(|node| match SumMerkleNode::create_proof_node(node) {
+                SumMerkleNode::ProofNode { end, data } => (H256::from_slice(&data), end),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+    fn from_proof_nodes(leaf_index: u64, proof_nodes: &[(H256, u64)]) -> Self {
+        let nodes: Vec<SumMerkleNode> = proof_nodes
+            .iter()
+            .map(|(hash, end)| SumMerkleNode::ProofNode {
+                end: *end,
+                data: Bytes::from(hash.as_bytes()),
+            })
+            .collect();
+        InclusionProof::new(leaf_index, &nodes)
+    }
+    /// ### proof.verify()
+    /// A function to verify that `leaf` is included in the tree of `root`
+    /// ```ignore
+    /// let is_included = proof.verify(&leaf, &block.get_root())
+    /// ```
+    pub fn verify(&self, leaf: &SumMerkleNode, root: &H256) -> bool {
+        SumMerkleTree::<Keccak256>::verify(
+            leaf,
+            self.leaf_index as usize,
+            self.nodes.clone(),
+            &Bytes::from(root.as_bytes()),
+        )
+        .is_ok()
+    }
+    /// ### proof.get_implicit_bounds()
+    /// A function to get the range which `leaf` covers in the tree of `root`.
+    /// Returns `None` if `leaf` is not included.
+    /// ```ignore
+    /// let bounds = proof.get_implicit_bounds(&leaf, &block.get_root())
+    /// ```
+    pub fn get_implicit_bounds(&self, leaf: &SumMerkleNode, root: &H256) -> Option<ImplicitBounds> {
+        SumMerkleTree::<Keccak256>::verify(
+            leaf,
+            self.leaf_index as usize,
+            self.nodes.clone(),
+            &Bytes::from(root.as_bytes()),
+        )
+        .ok()
+    }
+    pub fn to_abi(&self) -> Vec<u8> {
+        let (hashes, ends): (Vec<Token>, Vec<Token>) = self
+            .get_proof_nodes()
+            .iter()
+            .map(|(hash, end)| {
+                (
+                    Token::FixedBytes(hash.as_bytes().to_vec()),
+                    Token::Uint((*end).into()),
+                )
+            })
+            .unzip();
+        ethabi::encode(&[
+            Token::Uint(self.leaf_index.into()),
+            Token::Array(ends),
+            Token::Array(hashes),
+        ])
+    }
+    pub fn from_abi(data: &[u8]) -> Result<Self, Error> {
+        let decoded: Vec<Token> = ethabi::decode(
+            &[
+                ethabi::ParamType::Uint(256),
+                ethabi::ParamType::Array(Box::new(ethabi::ParamType::Uint(256))),
+                ethabi::ParamType::Array(Box::new(ethabi::ParamType::FixedBytes(32))),
+            ],
+            data,
+        )
+        .map_err(|_e| Error::from(ErrorKind::AbiDecode))?;
+        let leaf_index = decoded[0].clone().to_uint().and_then(to_u64);
+        let ends = decoded[1].clone().to_array();
+        let hashes = decoded[2].clone().to_array();
+        if let (Some(leaf_index), Some(ends), Some(hashes)) = (leaf_index, ends, hashes) {
+            if ends.len() != hashes.len() {
+                return Err(Error::from(ErrorKind::AbiDecode));
+            }
+            let proof_nodes: Option<Vec<(H256, u64)>> = hashes
+                .into_iter()
+                .zip(ends.into_iter())
+                .map(|(hash, end)| -> Option<(H256, u64)> {
+                    let end = end.to_uint().and_then(to_u64)?;
+                    let hash = hash.to_fixed_bytes()?;
+                    Some((H256::from_slice(&hash), end))
+                })
+                .collect();
+            proof_nodes
+                .map(|proof_nodes| InclusionProof::from_proof_nodes(leaf_index, &proof_nodes))
+                .ok_or_else(|| Error::from(ErrorKind::AbiDecode))
+        } else {
+            Err(Error::from(ErrorKind::AbiDecode))
+        }
+    }
+}
+
+/// ends and indices of the tree are u64, so larger values can not be decoded
+fn to_u64(value: U256) -> Option<u64> {
+    if value > U256::from(u64::max_value()) {
+        None
+    } else {
+        Some(value.as_u64())
+    }
+}
+
+impl Encodable for InclusionProof {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        let proof_nodes = self.get_proof_nodes();
+        s.begin_list(2);
+        s.append(&self.leaf_index);
+        s.begin_list(proof_nodes.len());
+        for (hash, end) in proof_nodes.iter() {
+            s.begin_list(2);
+            s.append(end);
+            s.append(&hash.as_bytes());
+        }
+    }
+}
+
+impl Decodable for InclusionProof {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let leaf_index: u64 = rlp.val_at(0)?;
+        let proof_nodes: Result<Vec<(H256, u64)>, DecoderError> = rlp
+            .at(1)?
+            .iter()
+            .map(|node| -> Result<(H256, u64), DecoderError> {
+                let end: u64 = node.val_at(0)?;
+                let hash: Vec<u8> = node.val_at(1)?;
+                if hash.len() != 32 {
+                    return Err(DecoderError::RlpInvalidLength);
+                }
+                Ok((H256::from_slice(&hash), end))
+            })
+            .collect();
+        Ok(InclusionProof::from_proof_nodes(leaf_index, &proof_nodes?))
+    }
+}
+
+/// a node of `InclusionProofJson`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProofNodeJson {
+    hash: String,
+    end: String,
+}
+
+/// JSON representation of an inclusion proof.
+/// Ends are decimal strings because JavaScript numbers can't hold every u64.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProofJson {
+    leaf_index: String,
+    nodes: Vec<ProofNodeJson>,
+}
+
+impl InclusionProofJson {
+    pub fn new(inclusion_proof: &InclusionProof) -> Self {
+        InclusionProofJson {
+            leaf_index: inclusion_proof.get_leaf_index().to_string(),
+            nodes: inclusion_proof
+                .get_proof_nodes()
+                .iter()
+                .map(|(hash, end)| ProofNodeJson {
+                    hash: format!("0x{}", hex::encode(hash.as_bytes())),
+                    end: end.to_string(),
+                })
+                .collect(),
+        }
+    }
+    pub fn to_inclusion_proof(&self) -> Result<InclusionProof, Error> {
+        let leaf_index = self
+            .leaf_index
+            .parse::<u64>()
+            .map_err(|_e| Error::from(ErrorKind::JsonDecode))?;
+        let proof_nodes: Result<Vec<(H256, u64)>, Error> = self
+            .nodes
+            .iter()
+            .map(|node| -> Result<(H256, u64), Error> {
+                let end = node
+                    .end
+                    .parse::<u64>()
+                    .map_err(|_e| Error::from(ErrorKind::JsonDecode))?;
+                let hash = hex::decode(node.hash.trim_start_matches("0x"))
+                    .map_err(|_e| Error::from(ErrorKind::JsonDecode))?;
+                if hash.len() != 32 {
+                    return Err(Error::from(ErrorKind::JsonDecode));
+                }
+                Ok((H256::from_slice(&hash), end))
+            })
+            .collect();
+        Ok(InclusionProof::from_proof_nodes(leaf_index, &proof_nodes?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InclusionProof, InclusionProofJson};
+    use bytes::Bytes;
+    use sum_merkle_tree::{SumMerkleNode, SumMerkleTree};
+
+    /// A proof of the second leaf in a tree of 4 leaves.
+    /// Golden vectors below are encoded by hand from the ABI and RLP specifications,
+    /// not generated by the JS library or contract tests, so they don't prove compatibility with them.
+    fn create_golden_proof() -> InclusionProof {
+        InclusionProof::new(
+            1,
+            &[
+                SumMerkleNode::ProofNode {
+                    end: 100,
+                    data: Bytes::from(&[0x11u8; 32][..]),
+                },
+                SumMerkleNode::ProofNode {
+                    end: 300,
+                    data: Bytes::from(&[0x22u8; 32][..]),
+                },
+            ],
+        )
+    }
+
+    const GOLDEN_ABI: &str = concat!(
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000060",
+        "00000000000000000000000000000000000000000000000000000000000000c0",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "0000000000000000000000000000000000000000000000000000000000000064",
+        "000000000000000000000000000000000000000000000000000000000000012c",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+        "1111111111111111111111111111111111111111111111111111111111111111",
+        "2222222222222222222222222222222222222222222222222222222222222222",
+    );
+
+    const GOLDEN_RLP: &str = concat!(
+        "f84b01f848",
+        "e264a0",
+        "1111111111111111111111111111111111111111111111111111111111111111",
+        "e482012ca0",
+        "2222222222222222222222222222222222222222222222222222222222222222",
+    );
+
+    const GOLDEN_JSON: &str = concat!(
+        r#"{"leafIndex":"1","nodes":["#,
+        r#"{"hash":"0x1111111111111111111111111111111111111111111111111111111111111111","end":"100"},"#,
+        r#"{"hash":"0x2222222222222222222222222222222222222222222222222222222222222222","end":"300"}"#,
+        r#"]}"#,
+    );
+
+    #[test]
+    fn test_abi_encode() {
+        let node = SumMerkleNode::ProofNode {
+            end: 100,
+            data: Bytes::from(&[1u8; 32][..]),
+        };
+        let inclusion_proof = InclusionProof::new(1, &[node]);
+        let encoded = inclusion_proof.to_abi();
+        let decoded = InclusionProof::from_abi(&encoded).unwrap();
+        assert_eq!(decoded, inclusion_proof);
+    }
+
+    #[test]
+    fn test_golden_abi() {
+        let inclusion_proof = create_golden_proof();
+        assert_eq!(hex::encode(inclusion_proof.to_abi()), GOLDEN_ABI);
+        let decoded = InclusionProof::from_abi(&hex::decode(GOLDEN_ABI).unwrap()).unwrap();
+        assert_eq!(decoded, inclusion_proof);
+    }
+
+    #[test]
+    fn test_golden_rlp() {
+        let inclusion_proof = create_golden_proof();
+        assert_eq!(hex::encode(rlp::encode(&inclusion_proof)), GOLDEN_RLP);
+        let decoded: InclusionProof = rlp::decode(&hex::decode(GOLDEN_RLP).unwrap()).unwrap();
+        assert_eq!(decoded, inclusion_proof);
+    }
+
+    #[test]
+    fn test_golden_json() {
+        let inclusion_proof = create_golden_proof();
+        let json = serde_json::to_string(&InclusionProofJson::new(&inclusion_proof)).unwrap();
+        assert_eq!(json, GOLDEN_JSON);
+        let decoded: InclusionProofJson = serde_json::from_str(GOLDEN_JSON).unwrap();
+        assert_eq!(decoded.to_inclusion_proof().unwrap(), inclusion_proof);
+    }
+
+    #[test]
+    fn test_encoded_proof_verifies() {
+        let leaves: Vec<SumMerkleNode> = (0..5)
+            .map(|i| SumMerkleNode::create_leaf((i + 1) * 100, Bytes::from(vec![i as u8; 4])))
+            .collect();
+        let tree: SumMerkleTree = SumMerkleTree::generate(&leaves);
+        let root = ethereum_types::H256::from_slice(&tree.get_root());
+        let nodes = tree.get_inclusion_proof(4, 5);
+        let inclusion_proof = InclusionProof::new(4, &nodes);
+        // the padding sibling has the largest end
+        assert!(inclusion_proof
+            .get_proof_nodes()
+            .iter()
+            .any(|(_, end)| *end == u64::max_value()));
+        let decoded_proofs = vec![
+            InclusionProof::from_abi(&inclusion_proof.to_abi()).unwrap(),
+            rlp::decode(&rlp::encode(&inclusion_proof)).unwrap(),
+            InclusionProofJson::new(&inclusion_proof)
+                .to_inclusion_proof()
+                .unwrap(),
+        ];
+        for decoded in decoded_proofs.iter() {
+            assert!(decoded.verify(&leaves[4], &root));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_abi() {
+        // lengths of ends and hashes don't match
+        let mut data = hex::decode(GOLDEN_ABI).unwrap();
+        data.truncate(32 * 8);
+        data[32 * 6 + 31] = 1;
+        assert!(InclusionProof::from_abi(&data).is_err());
+        // an end which doesn't fit in u64
+        let mut data = hex::decode(GOLDEN_ABI).unwrap();
+        data[32 * 4] = 1;
+        assert!(InclusionProof::from_abi(&data).is_err());
+    }
+}
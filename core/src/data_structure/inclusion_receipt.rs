@@ -0,0 +1,121 @@
+extern crate ethabi;
+extern crate tiny_keccak;
+
+use super::error::{Error, ErrorKind};
+use super::signature::{SecretKey, Signature};
+use ethabi::Token;
+use ethereum_types::{Address, H256};
+use tiny_keccak::keccak256;
+
+#[derive(Clone, Debug, PartialEq)]
+/// ## struct InclusionReceipt
+/// - has a `transaction_hash` of an accepted transaction
+/// - has a `block_number` which the operator promises to include the transaction in
+/// - has an `operator_signature` over both
+pub struct InclusionReceipt {
+    transaction_hash: H256,
+    block_number: u64,
+    operator_signature: Signature,
+}
+
+impl InclusionReceipt {
+    /// ### InclusionReceipt.sign
+    /// A constructor of an InclusionReceipt struct signed by operator
+    /// ```ignore
+    /// let receipt = InclusionReceipt.sign(&tx.hash(), block_number, &operator_key)
+    /// ```
+    pub fn sign(
+        transaction_hash: &H256,
+        block_number: u64,
+        operator_key: &SecretKey,
+    ) -> Result<Self, Error> {
+        let hash = Self::compute_hash(transaction_hash, block_number);
+        Ok(InclusionReceipt {
+            transaction_hash: *transaction_hash,
+            block_number,
+            operator_signature: Signature::sign(&hash, operator_key)?,
+        })
+    }
+    fn compute_hash(transaction_hash: &H256, block_number: u64) -> H256 {
+        H256::from(keccak256(&ethabi::encode(&[
+            Token::FixedBytes(transaction_hash.as_bytes().to_vec()),
+            Token::Uint(block_number.into()),
+        ])))
+    }
+    pub fn get_transaction_hash(&self) -> &H256 {
+        &self.transaction_hash
+    }
+    pub fn get_block_number(&self) -> u64 {
+        self.block_number
+    }
+    pub fn get_operator_signature(&self) -> &Signature {
+        &self.operator_signature
+    }
+    /// ### receipt.verify()
+    /// A function to check whether `operator` signed the receipt
+    pub fn verify(&self, operator: &Address) -> Result<(), Error> {
+        let hash = Self::compute_hash(&self.transaction_hash, self.block_number);
+        if &self.operator_signature.recover(&hash)? == operator {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::InvalidSignature))
+        }
+    }
+    pub fn to_abi(&self) -> Vec<u8> {
+        ethabi::encode(&[
+            Token::FixedBytes(self.transaction_hash.as_bytes().to_vec()),
+            Token::Uint(self.block_number.into()),
+            Token::Bytes(self.operator_signature.to_bytes()),
+        ])
+    }
+    pub fn from_abi(data: &[u8]) -> Result<Self, Error> {
+        let decoded: Vec<Token> = ethabi::decode(
+            &[
+                ethabi::ParamType::FixedBytes(32),
+                ethabi::ParamType::Uint(8),
+                ethabi::ParamType::Bytes,
+            ],
+            data,
+        )
+        .map_err(|_e| Error::from(ErrorKind::AbiDecode))?;
+        let transaction_hash = decoded[0].clone().to_fixed_bytes();
+        let block_number = decoded[1].clone().to_uint();
+        let operator_signature = decoded[2].clone().to_bytes();
+        if let (Some(transaction_hash), Some(block_number), Some(operator_signature)) =
+            (transaction_hash, block_number, operator_signature)
+        {
+            Ok(InclusionReceipt {
+                transaction_hash: H256::from_slice(&transaction_hash),
+                block_number: block_number.as_u64(),
+                operator_signature: Signature::from_bytes(&operator_signature)?,
+            })
+        } else {
+            Err(Error::from(ErrorKind::AbiDecode))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InclusionReceipt;
+    use crate::data_structure::signature::{SecretKey, Signature};
+    use ethereum_types::{Address, H256};
+
+    #[test]
+    fn test_verify() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let receipt = InclusionReceipt::sign(&H256::repeat_byte(1), 2, &operator_key).unwrap();
+        assert!(receipt
+            .verify(&Signature::address_of(&operator_key))
+            .is_ok());
+        assert!(receipt.verify(&Address::zero()).is_err());
+    }
+
+    #[test]
+    fn test_abi_encode() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let receipt = InclusionReceipt::sign(&H256::repeat_byte(1), 2, &operator_key).unwrap();
+        let decoded = InclusionReceipt::from_abi(&receipt.to_abi()).unwrap();
+        assert_eq!(decoded, receipt);
+    }
+}
@@ -0,0 +1,116 @@
+extern crate secp256k1;
+extern crate tiny_keccak;
+
+use super::error::{Error, ErrorKind};
+use ethereum_types::{Address, H256};
+pub use secp256k1::SecretKey;
+use secp256k1::{Message, PublicKey, RecoveryId};
+use tiny_keccak::keccak256;
+
+#[derive(Clone, Debug, PartialEq)]
+/// ## struct Signature
+/// - has `r`, `s` and recovery id `v` of a secp256k1 signature
+/// - Signer's address is recovered from the signature and the signed hash
+pub struct Signature {
+    r: H256,
+    s: H256,
+    v: u8,
+}
+
+impl Signature {
+    /// ### Signature.sign
+    /// A static function to sign a 32 bytes hash with a secret key
+    /// ```ignore
+    /// let signature = Signature.sign(&hash, &secret_key)
+    /// ```
+    pub fn sign(hash: &H256, secret_key: &SecretKey) -> Result<Self, Error> {
+        let (signature, recovery_id) =
+            secp256k1::sign(&Message::parse(hash.as_fixed_bytes()), secret_key)
+                .map_err(|_e| Error::from(ErrorKind::InvalidSignature))?;
+        let serialized = signature.serialize();
+        Ok(Signature {
+            r: H256::from_slice(&serialized[0..32]),
+            s: H256::from_slice(&serialized[32..64]),
+            v: recovery_id.serialize(),
+        })
+    }
+    /// ### signature.recover
+    /// A function to recover the address of the signer
+    /// ```ignore
+    /// let signer = signature.recover(&hash)
+    /// ```
+    pub fn recover(&self, hash: &H256) -> Result<Address, Error> {
+        let signature = secp256k1::Signature::parse_slice(&self.to_bytes()[0..64])
+            .map_err(|_e| Error::from(ErrorKind::InvalidSignature))?;
+        let recovery_id =
+            RecoveryId::parse(self.v).map_err(|_e| Error::from(ErrorKind::InvalidSignature))?;
+        secp256k1::recover(
+            &Message::parse(hash.as_fixed_bytes()),
+            &signature,
+            &recovery_id,
+        )
+        .map(|public_key| Signature::to_address(&public_key))
+        .map_err(|_e| Error::from(ErrorKind::InvalidSignature))
+    }
+    /// ### Signature.address_of
+    /// A static function to get the address of a secret key
+    /// ```ignore
+    /// let address = Signature.address_of(&secret_key)
+    /// ```
+    pub fn address_of(secret_key: &SecretKey) -> Address {
+        Signature::to_address(&PublicKey::from_secret_key(secret_key))
+    }
+    fn to_address(public_key: &PublicKey) -> Address {
+        // skip the prefix of uncompressed public key
+        let hash = keccak256(&public_key.serialize()[1..]);
+        Address::from_slice(&hash[12..])
+    }
+    /// ### signature.to_bytes
+    /// A function to serialize the signature as `r ++ s ++ v`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(65);
+        bytes.extend_from_slice(self.r.as_bytes());
+        bytes.extend_from_slice(self.s.as_bytes());
+        bytes.push(self.v);
+        bytes
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 65 {
+            return Err(Error::from(ErrorKind::InvalidSignature));
+        }
+        Ok(Signature {
+            r: H256::from_slice(&bytes[0..32]),
+            s: H256::from_slice(&bytes[32..64]),
+            v: bytes[64],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SecretKey, Signature};
+    use ethereum_types::H256;
+
+    #[test]
+    fn test_sign_and_recover() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let hash = H256::repeat_byte(2);
+        let signature = Signature::sign(&hash, &secret_key).unwrap();
+        assert_eq!(
+            signature.recover(&hash).unwrap(),
+            Signature::address_of(&secret_key)
+        );
+        assert_ne!(
+            signature.recover(&H256::repeat_byte(3)).unwrap_or_default(),
+            Signature::address_of(&secret_key)
+        );
+    }
+
+    #[test]
+    fn test_bytes() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let signature = Signature::sign(&H256::repeat_byte(2), &secret_key).unwrap();
+        let decoded = Signature::from_bytes(&signature.to_bytes()).unwrap();
+        assert_eq!(decoded, signature);
+    }
+}
@@ -0,0 +1,116 @@
+extern crate ethabi;
+extern crate tiny_keccak;
+
+use super::error::{Error, ErrorKind};
+use super::signature::{SecretKey, Signature};
+use ethabi::Token;
+use ethereum_types::{Address, H256};
+use tiny_keccak::keccak256;
+
+#[derive(Clone, Debug, PartialEq)]
+/// ## struct SignedRoot
+/// - has a `block_number`
+/// - has a merkle `root` of the block
+/// - has a `signature` of the operator over `block_number` and `root`
+pub struct SignedRoot {
+    block_number: u64,
+    root: H256,
+    signature: Signature,
+}
+
+impl SignedRoot {
+    /// ### SignedRoot.sign
+    /// A static function to sign a root of a block with operator's secret key
+    /// ```ignore
+    /// let signed_root = SignedRoot.sign(block_number, &root, &secret_key)
+    /// ```
+    pub fn sign(block_number: u64, root: &H256, secret_key: &SecretKey) -> Result<Self, Error> {
+        let signature = Signature::sign(&Self::hash(block_number, root), secret_key)?;
+        Ok(SignedRoot {
+            block_number,
+            root: *root,
+            signature,
+        })
+    }
+    fn hash(block_number: u64, root: &H256) -> H256 {
+        H256::from(keccak256(&ethabi::encode(&[
+            Token::Uint(block_number.into()),
+            Token::FixedBytes(root.as_bytes().to_vec()),
+        ])))
+    }
+    pub fn get_block_number(&self) -> u64 {
+        self.block_number
+    }
+    pub fn get_root(&self) -> &H256 {
+        &self.root
+    }
+    pub fn get_signature(&self) -> &Signature {
+        &self.signature
+    }
+    /// ### signed_root.recover
+    /// A function to recover the address of the signer
+    pub fn recover(&self) -> Result<Address, Error> {
+        self.signature
+            .recover(&Self::hash(self.block_number, &self.root))
+    }
+    /// ### signed_root.verify
+    /// A function to check whether `operator` signed this root
+    pub fn verify(&self, operator: &Address) -> bool {
+        self.recover()
+            .map(|signer| &signer == operator)
+            .unwrap_or(false)
+    }
+    pub fn to_abi(&self) -> Vec<u8> {
+        ethabi::encode(&[
+            Token::Uint(self.block_number.into()),
+            Token::FixedBytes(self.root.as_bytes().to_vec()),
+            Token::Bytes(self.signature.to_bytes()),
+        ])
+    }
+    pub fn from_abi(data: &[u8]) -> Result<Self, Error> {
+        let decoded: Vec<Token> = ethabi::decode(
+            &[
+                ethabi::ParamType::Uint(8),
+                ethabi::ParamType::FixedBytes(32),
+                ethabi::ParamType::Bytes,
+            ],
+            data,
+        )
+        .map_err(|_e| Error::from(ErrorKind::AbiDecode))?;
+        let block_number = decoded[0].clone().to_uint();
+        let root = decoded[1].clone().to_fixed_bytes();
+        let signature = decoded[2].clone().to_bytes();
+        if let (Some(block_number), Some(root), Some(signature)) = (block_number, root, signature) {
+            Ok(SignedRoot {
+                block_number: block_number.as_u64(),
+                root: H256::from_slice(&root),
+                signature: Signature::from_bytes(&signature)?,
+            })
+        } else {
+            Err(Error::from(ErrorKind::AbiDecode))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedRoot;
+    use crate::data_structure::signature::{SecretKey, Signature};
+    use ethereum_types::{Address, H256};
+
+    #[test]
+    fn test_verify() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let signed_root = SignedRoot::sign(1, &H256::repeat_byte(2), &secret_key).unwrap();
+        assert!(signed_root.verify(&Signature::address_of(&secret_key)));
+        assert!(!signed_root.verify(&Address::zero()));
+    }
+
+    #[test]
+    fn test_abi_encode() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let signed_root = SignedRoot::sign(1, &H256::repeat_byte(2), &secret_key).unwrap();
+        let decoded = SignedRoot::from_abi(&signed_root.to_abi()).unwrap();
+        assert_eq!(decoded, signed_root);
+    }
+}
@@ -50,6 +50,9 @@ impl StateObject {
     pub fn get_predicate(&self) -> &Address {
         &self.predicate
     }
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
 }
 
 #[cfg(test)]
@@ -89,6 +89,9 @@ impl StateUpdate {
     pub fn get_state_object(&self) -> &StateObject {
         &self.state_object
     }
+    pub fn get_plasma_contract(&self) -> &Address {
+        &self.plasma_contract
+    }
 }
 
 #[cfg(test)]
@@ -3,25 +3,56 @@ extern crate rlp;
 extern crate tiny_keccak;
 
 use super::error::{Error, ErrorKind};
+use super::signature::{SecretKey, Signature};
 use ethabi::Token;
 use ethereum_types::{Address, H256};
 use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
-use tiny_keccak::Keccak;
+use tiny_keccak::{keccak256, Keccak};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+/// ## struct Witness
+/// - has `r`, `s` and recovery id `v` of the signature over the body of a transaction
 pub struct Witness {
-    v: H256,
     r: H256,
-    s: u64,
+    s: H256,
+    v: u64,
 }
 
 impl Witness {
-    pub fn new(v: H256, r: H256, s: u64) -> Self {
-        Witness { v, r, s }
+    pub fn new(r: H256, s: H256, v: u64) -> Self {
+        Witness { r, s, v }
+    }
+    /// ### Witness.sign
+    /// A static function to sign the body hash of a transaction
+    /// ```ignore
+    /// let witness = Witness.sign(&tx.get_body_hash(), &secret_key)
+    /// ```
+    pub fn sign(hash: &H256, secret_key: &SecretKey) -> Result<Self, Error> {
+        let bytes = Signature::sign(hash, secret_key)?.to_bytes();
+        Ok(Witness::new(
+            H256::from_slice(&bytes[0..32]),
+            H256::from_slice(&bytes[32..64]),
+            u64::from(bytes[64]),
+        ))
+    }
+    /// ### witness.recover
+    /// A function to recover the address of the signer
+    /// ```ignore
+    /// let signer = witness.recover(&tx.get_body_hash())
+    /// ```
+    pub fn recover(&self, hash: &H256) -> Result<Address, Error> {
+        if self.v > u64::from(u8::max_value()) {
+            return Err(Error::from(ErrorKind::InvalidSignature));
+        }
+        let mut bytes = Vec::with_capacity(65);
+        bytes.extend_from_slice(self.r.as_bytes());
+        bytes.extend_from_slice(self.s.as_bytes());
+        bytes.push(self.v as u8);
+        Signature::from_bytes(&bytes)?.recover(hash)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 /// ## struct Transaction
 /// - has a `plasma_contract_address`
 /// - has a `start` (A range element)
@@ -90,9 +121,9 @@ impl Transaction {
             Token::Uint(self.end.into()),
             Token::FixedBytes(vec![self.method_id]),
             Token::Bytes(self.parameters.clone()),
-            Token::FixedBytes(self.witness.v.as_bytes().to_vec()),
             Token::FixedBytes(self.witness.r.as_bytes().to_vec()),
-            Token::Uint(self.witness.s.into()),
+            Token::FixedBytes(self.witness.s.as_bytes().to_vec()),
+            Token::Uint(self.witness.v.into()),
         ])
     }
     /// ### Transaction.from_abi()
@@ -120,27 +151,27 @@ impl Transaction {
         let end = decoded[2].clone().to_uint();
         let method_id_opt = decoded[3].clone().to_fixed_bytes();
         let parameters = decoded[4].clone().to_bytes();
-        let v = decoded[5].clone().to_fixed_bytes();
-        let r = decoded[6].clone().to_fixed_bytes();
-        let s = decoded[7].clone().to_uint();
+        let r = decoded[5].clone().to_fixed_bytes();
+        let s = decoded[6].clone().to_fixed_bytes();
+        let v = decoded[7].clone().to_uint();
         if let (
             Some(plasma_contract),
             Some(start),
             Some(end),
             Some(method_id),
             Some(parameters),
-            Some(v),
             Some(r),
             Some(s),
+            Some(v),
         ) = (
             plasma_contract,
             start,
             end,
             method_id_opt,
             parameters,
-            v,
             r,
             s,
+            v,
         ) {
             Ok(Transaction::new(
                 plasma_contract,
@@ -148,12 +179,38 @@ impl Transaction {
                 end.as_u64(),
                 method_id[0],
                 &parameters,
-                &Witness::new(H256::from_slice(&v), H256::from_slice(&r), s.as_u64()),
+                &Witness::new(H256::from_slice(&r), H256::from_slice(&s), v.as_u64()),
             ))
         } else {
             Err(Error::from(ErrorKind::AbiDecode))
         }
     }
+    /// ### tx.hash()
+    /// A function to get keccak256 hash of the full abi bytes
+    /// ```ignore
+    /// let hash = tx.hash()
+    /// ```
+    pub fn hash(&self) -> H256 {
+        H256::from(keccak256(&self.to_abi()))
+    }
+    /// ### tx.get_body_hash()
+    /// A function to get keccak256 hash of the body abi bytes, which is signed by the witness
+    /// ```ignore
+    /// let body_hash = tx.get_body_hash()
+    /// ```
+    pub fn get_body_hash(&self) -> H256 {
+        H256::from(keccak256(&self.to_body_abi()))
+    }
+    /// ### tx.sign()
+    /// A function to get the transaction whose witness is the signature of its body
+    /// ```ignore
+    /// let signed_tx = tx.sign(&secret_key)?
+    /// ```
+    pub fn sign(&self, secret_key: &SecretKey) -> Result<Self, Error> {
+        let mut signed = self.clone();
+        signed.witness = Witness::sign(&self.get_body_hash(), secret_key)?;
+        Ok(signed)
+    }
     /// ### Transaction.create_method_id()
     /// A static function to generate method_id bytes from value
     /// ```ignore
@@ -182,9 +239,20 @@ impl Transaction {
     pub fn get_end(&self) -> u64 {
         self.end
     }
+    /// ### tx.get_plasma_contract_address()
+    /// A function to get plasma contract address of a tx instance
+    /// ```ignore
+    /// let plasma_contract_address = tx.get_plasma_contract_address();
+    /// ```
+    pub fn get_plasma_contract_address(&self) -> &Address {
+        &self.plasma_contract_address
+    }
     pub fn get_parameters(&self) -> &[u8] {
         &self.parameters
     }
+    pub fn get_witness(&self) -> &Witness {
+        &self.witness
+    }
 }
 
 impl Encodable for Transaction {
@@ -205,6 +273,7 @@ impl Decodable for Transaction {
 mod tests {
     use super::Transaction;
     use super::Witness;
+    use crate::data_structure::signature::{SecretKey, Signature};
     use ethereum_types::{Address, H256};
 
     #[test]
@@ -223,4 +292,28 @@ mod tests {
         assert_eq!(decoded.start, transaction.start);
     }
 
+    #[test]
+    fn test_sign() {
+        let secret_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let transaction = Transaction::new(
+            Address::zero(),
+            0,
+            100,
+            Transaction::create_method_id(&b"send(address)"[..]),
+            &b"parameters"[..],
+            &Witness::new(H256::zero(), H256::zero(), 0),
+        )
+        .sign(&secret_key)
+        .unwrap();
+        let decoded = Transaction::from_abi(&transaction.to_abi()).unwrap();
+        assert_eq!(decoded, transaction);
+        assert_eq!(
+            decoded
+                .get_witness()
+                .recover(&decoded.get_body_hash())
+                .unwrap(),
+            Signature::address_of(&secret_key)
+        );
+    }
+
 }
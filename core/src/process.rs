@@ -1,3 +1,7 @@
 mod block_generator;
+mod header_verifier;
+mod state_verifier;
 
 pub use self::block_generator::BlockGenerator;
+pub use self::header_verifier::HeaderVerifier;
+pub use self::state_verifier::StateVerifier;
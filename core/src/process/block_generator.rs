@@ -1,16 +1,42 @@
 extern crate ethereum_types;
 
 use crate::data_structure::block::Block;
+use crate::data_structure::block_header::BlockHeader;
 use crate::data_structure::error::Error;
+use crate::data_structure::signature::SecretKey;
 use crate::data_structure::transaction::Transaction;
 use ethereum_types::H256;
+use sum_merkle_tree::SumMerkleTree;
 
 pub struct BlockGenerator {}
 
 impl BlockGenerator {
-    pub fn generate(transactions: &[Transaction]) -> Result<Block, Error> {
-        // TODO: caluculate merkle root
-        // copy all transactions
-        Ok(Block::new(transactions, H256::zero()))
+    /// Generate a block whose header is signed by `operator_key`
+    pub fn generate(
+        transactions: &[Transaction],
+        number: u64,
+        prev_hash: &H256,
+        timestamp: u64,
+        operator_key: &SecretKey,
+    ) -> Result<Block, Error> {
+        Self::generate_with_tree(transactions, number, prev_hash, timestamp, operator_key)
+            .map(|(block, _)| block)
+    }
+    /// Generate a block, and returns the tree of its transactions with it.
+    /// An empty block has no tree.
+    pub fn generate_with_tree(
+        transactions: &[Transaction],
+        number: u64,
+        prev_hash: &H256,
+        timestamp: u64,
+        operator_key: &SecretKey,
+    ) -> Result<(Block, Option<SumMerkleTree>), Error> {
+        // leaves of sum merkle tree should be sorted by range
+        let mut sorted = transactions.to_vec();
+        sorted.sort_by_key(|transaction| transaction.get_start());
+        let tree = Block::compute_tree(&sorted);
+        let root = Block::get_root_of(tree.as_ref());
+        let header = BlockHeader::sign(number, &root, prev_hash, timestamp, operator_key)?;
+        Ok((Block::new(&header, &sorted), tree))
     }
 }
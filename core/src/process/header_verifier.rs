@@ -0,0 +1,112 @@
+use crate::data_structure::error::{Error, ErrorKind};
+use crate::data_structure::{Block, BlockHeader};
+use ethereum_types::Address;
+use sum_merkle_tree::SumMerkleTree;
+
+/// HeaderVerifier pins blocks to an operator key before they are confirmed on L1
+pub struct HeaderVerifier {}
+
+impl HeaderVerifier {
+    /// Verify that `block` is signed by `operator` and its root matches its transactions
+    pub fn verify_block(block: &Block, operator: &Address) -> Result<(), Error> {
+        Self::verify_block_tree(
+            block,
+            Block::compute_tree(block.get_transactions()).as_ref(),
+            operator,
+        )
+    }
+
+    /// Verify that `block` is signed by `operator` and its root matches `tree`,
+    /// which is built from its transactions by `Block::compute_tree`
+    pub fn verify_block_tree(
+        block: &Block,
+        tree: Option<&SumMerkleTree>,
+        operator: &Address,
+    ) -> Result<(), Error> {
+        block.get_header().verify(operator)?;
+        if &Block::get_root_of(tree) == block.get_root() {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::InvalidBlockHeader))
+        }
+    }
+
+    /// Verify that `header` is signed by `operator` and follows `prev`
+    pub fn verify_next(
+        prev: &BlockHeader,
+        header: &BlockHeader,
+        operator: &Address,
+    ) -> Result<(), Error> {
+        header.verify(operator)?;
+        if header.get_number() == prev.get_number() + 1 && header.get_prev_hash() == &prev.hash() {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::InvalidBlockHeader))
+        }
+    }
+
+    /// Verify that `headers` are a chain signed by `operator`
+    pub fn verify_chain(headers: &[BlockHeader], operator: &Address) -> Result<(), Error> {
+        if let Some(first) = headers.first() {
+            first.verify(operator)?;
+        }
+        headers
+            .windows(2)
+            .map(|pair| Self::verify_next(&pair[0], &pair[1], operator))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HeaderVerifier;
+    use crate::data_structure::signature::{SecretKey, Signature};
+    use crate::data_structure::BlockHeader;
+    use ethereum_types::H256;
+
+    fn create_chain(operator_key: &SecretKey, length: u64) -> Vec<BlockHeader> {
+        let mut headers: Vec<BlockHeader> = vec![];
+        for number in 1..=length {
+            let prev_hash = headers.last().map_or_else(H256::zero, BlockHeader::hash);
+            headers.push(
+                BlockHeader::sign(
+                    number,
+                    &H256::repeat_byte(number as u8),
+                    &prev_hash,
+                    number,
+                    operator_key,
+                )
+                .unwrap(),
+            );
+        }
+        headers
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let operator = Signature::address_of(&operator_key);
+        let headers = create_chain(&operator_key, 3);
+        assert!(HeaderVerifier::verify_chain(&headers, &operator).is_ok());
+    }
+
+    #[test]
+    fn test_verify_broken_chain() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let operator = Signature::address_of(&operator_key);
+        let mut headers = create_chain(&operator_key, 3);
+        headers[2] =
+            BlockHeader::sign(3, &H256::repeat_byte(3), &H256::zero(), 3, &operator_key).unwrap();
+        assert!(HeaderVerifier::verify_chain(&headers, &operator).is_err());
+    }
+
+    #[test]
+    fn test_verify_chain_signed_by_others() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let other_key = SecretKey::parse(&[2u8; 32]).unwrap();
+        let headers = create_chain(&other_key, 2);
+        assert!(
+            HeaderVerifier::verify_chain(&headers, &Signature::address_of(&operator_key)).is_err()
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::data_structure::error::{Error, ErrorKind};
+use crate::data_structure::{Block, InclusionProof, StateUpdate, Transaction};
+use ethereum_types::H256;
+
+/// StateVerifier checks transactions included in blocks against prior states
+pub struct StateVerifier {}
+
+impl StateVerifier {
+    /// Verify that `transaction` is included in the block of `root`
+    pub fn verify_inclusion(
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+        root: &H256,
+    ) -> Result<(), Error> {
+        if inclusion_proof.verify(&Block::create_leaf(transaction), root) {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::InvalidInclusionProof))
+        }
+    }
+
+    /// Verify that `transaction` is included in the block of `root`,
+    /// and returns the range `[start, end)` which its leaf covers
+    pub fn verify_implicit_bounds(
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+        root: &H256,
+    ) -> Result<(u64, u64), Error> {
+        inclusion_proof
+            .get_implicit_bounds(&Block::create_leaf(transaction), root)
+            .map(|bounds| (bounds.get_implicit_start(), bounds.get_implicit_end()))
+            .ok_or_else(|| Error::from(ErrorKind::InvalidInclusionProof))
+    }
+
+    /// Verify that `[start, end)` is not touched in the block of `root`,
+    /// by the leaf of `transaction` which covers the range without touching it
+    pub fn verify_exclusion(
+        start: u64,
+        end: u64,
+        transaction: &Transaction,
+        inclusion_proof: &InclusionProof,
+        root: &H256,
+    ) -> Result<(), Error> {
+        let (implicit_start, implicit_end) =
+            Self::verify_implicit_bounds(transaction, inclusion_proof, root)?;
+        let is_covered = implicit_start <= start && end <= implicit_end;
+        let is_touched = transaction.get_start() < end && start < transaction.get_end();
+        if is_covered && !is_touched {
+            Ok(())
+        } else {
+            Err(Error::from(ErrorKind::InvalidExclusionProof))
+        }
+    }
+
+    /// Verify that `transaction` can spend `prior_state_update`
+    pub fn verify_transition(
+        prior_state_update: &StateUpdate,
+        transaction: &Transaction,
+    ) -> Result<(), Error> {
+        if prior_state_update.get_plasma_contract() != transaction.get_plasma_contract_address() {
+            return Err(Error::from(ErrorKind::InvalidStateTransition));
+        }
+        if transaction.get_start() >= prior_state_update.get_end()
+            || prior_state_update.get_start() >= transaction.get_end()
+        {
+            return Err(Error::from(ErrorKind::InvalidStateTransition));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StateVerifier;
+    use crate::data_structure::signature::SecretKey;
+    use crate::data_structure::{StateObject, StateUpdate, Transaction, Witness};
+    use crate::process::BlockGenerator;
+    use ethereum_types::{Address, H256};
+
+    fn create_transaction(plasma_contract: Address, start: u64, end: u64) -> Transaction {
+        Transaction::new(
+            plasma_contract,
+            start,
+            end,
+            Transaction::create_method_id(&b"send(address)"[..]),
+            &b"parameters"[..],
+            &Witness::new(H256::zero(), H256::zero(), 0),
+        )
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        let transactions = vec![
+            create_transaction(Address::zero(), 0, 100),
+            create_transaction(Address::zero(), 100, 200),
+        ];
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let block =
+            BlockGenerator::generate(&transactions, 1, &H256::zero(), 0, &operator_key).unwrap();
+        let proof = block.get_inclusion_proof(1).unwrap();
+        assert!(
+            StateVerifier::verify_inclusion(&transactions[1], &proof, block.get_root()).is_ok()
+        );
+        assert!(
+            StateVerifier::verify_inclusion(&transactions[0], &proof, block.get_root()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_exclusion() {
+        let transactions = vec![
+            create_transaction(Address::zero(), 0, 100),
+            create_transaction(Address::zero(), 150, 200),
+            create_transaction(Address::zero(), 200, 300),
+        ];
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let block =
+            BlockGenerator::generate(&transactions, 1, &H256::zero(), 0, &operator_key).unwrap();
+        let root = block.get_root();
+        // a gap between transactions, and a range after the last transaction
+        for (start, end, index) in [(100, 150, 1), (300, 400, 2)].iter() {
+            let proof = block.get_exclusion_proof(*start, *end).unwrap();
+            assert_eq!(proof.get_leaf_index(), *index);
+            assert!(StateVerifier::verify_exclusion(
+                *start,
+                *end,
+                &transactions[*index as usize],
+                &proof,
+                root
+            )
+            .is_ok());
+        }
+        assert!(block.get_exclusion_proof(120, 180).is_none());
+        assert!(block.get_exclusion_proof(50, 100).is_none());
+        // the leaf of the first transaction doesn't cover the gap
+        assert!(StateVerifier::verify_exclusion(
+            100,
+            150,
+            &transactions[0],
+            &block.get_inclusion_proof(0).unwrap(),
+            root
+        )
+        .is_err());
+        // the transaction touches the range
+        assert!(StateVerifier::verify_exclusion(
+            150,
+            160,
+            &transactions[1],
+            &block.get_inclusion_proof(1).unwrap(),
+            root
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_transition() {
+        let state_update = StateUpdate::new(
+            &StateObject::new(Address::zero(), &b"data"[..]),
+            0,
+            100,
+            1,
+            Address::zero(),
+        );
+        assert!(StateVerifier::verify_transition(
+            &state_update,
+            &create_transaction(Address::zero(), 50, 150)
+        )
+        .is_ok());
+        assert!(StateVerifier::verify_transition(
+            &state_update,
+            &create_transaction(Address::zero(), 100, 150)
+        )
+        .is_err());
+        assert!(StateVerifier::verify_transition(
+            &state_update,
+            &create_transaction(Address::from_low_u64_be(1), 0, 100)
+        )
+        .is_err());
+    }
+}
@@ -1 +1,4 @@
 pub mod kvdb;
+/// leveldb implementation for key value store
+#[cfg(feature = "leveldb")]
+pub mod leveldb;
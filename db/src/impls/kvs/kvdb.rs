@@ -37,8 +37,15 @@ impl KeyValueStore for CoreDb {
     fn has(&self, _key: &[u8]) -> Result<bool, Error> {
         Ok(true)
     }
-    fn batch(&self, _operations: &[Batch]) -> Result<(), Error> {
-        Ok(())
+    fn batch(&self, operations: &[Batch]) -> Result<(), Error> {
+        let mut tr = DBTransaction::new();
+        for operation in operations.iter() {
+            match operation {
+                Batch::BatchPut { key, value } => tr.put(None, key, value),
+                Batch::BatchDel { key } => tr.delete(None, key),
+            }
+        }
+        self.db.write(tr).map_err(Into::into)
     }
     fn iterator(&self, _prefix: &[u8]) -> Result<Box<KvsIterator + 'static>, Error> {
         Err(Error::from(ErrorKind::Dammy))
@@ -0,0 +1,120 @@
+extern crate leveldb;
+
+use crate::error::{Error, ErrorKind};
+use crate::traits::db::DatabaseTrait;
+use crate::traits::kvs::{Batch, KeyValueStore, KvsIterator};
+use db_key::Key;
+use leveldb::batch::Batch as LevelBatch;
+use leveldb::database::batch::Writebatch;
+use leveldb::database::Database;
+use leveldb::kv::KV;
+use leveldb::options::{Options, ReadOptions, WriteOptions};
+use std::path::Path;
+
+/// Key of leveldb as raw bytes
+pub struct BytesKey(Vec<u8>);
+
+impl Key for BytesKey {
+    fn from_u8(key: &[u8]) -> Self {
+        BytesKey(key.to_vec())
+    }
+    fn as_slice<T, F: Fn(&[u8]) -> T>(&self, f: F) -> T {
+        f(&self.0)
+    }
+}
+
+/// KeyValueStore persisted in a directory by leveldb
+pub struct KvsLevelImpl {
+    db: Database<BytesKey>,
+}
+
+impl DatabaseTrait for KvsLevelImpl {
+    /// `dbname` is a path of the directory
+    fn open(dbname: &str) -> Self {
+        Self::open_path(Path::new(dbname)).expect("Unable to open leveldb.")
+    }
+    fn close(&self) {}
+}
+
+impl KvsLevelImpl {
+    pub fn open_path(path: &Path) -> Result<Self, Error> {
+        let mut options = Options::new();
+        options.create_if_missing = true;
+        Ok(Self {
+            db: Database::open(path, options)?,
+        })
+    }
+}
+
+impl KeyValueStore for KvsLevelImpl {
+    fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>, Error> {
+        self.db
+            .get(ReadOptions::new(), BytesKey::from_u8(key))
+            .map(|v| v.map(Vec::into_boxed_slice))
+            .map_err(Into::into)
+    }
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        let mut options = WriteOptions::new();
+        options.sync = true;
+        self.db
+            .put(options, BytesKey::from_u8(key), value)
+            .map_err(Into::into)
+    }
+    fn del(&self, key: &[u8]) -> Result<(), Error> {
+        let mut options = WriteOptions::new();
+        options.sync = true;
+        self.db
+            .delete(options, BytesKey::from_u8(key))
+            .map_err(Into::into)
+    }
+    fn has(&self, key: &[u8]) -> Result<bool, Error> {
+        self.get(key).map(|v| v.is_some())
+    }
+    fn batch(&self, operations: &[Batch]) -> Result<(), Error> {
+        let mut batch = Writebatch::new();
+        for operation in operations.iter() {
+            match operation {
+                Batch::BatchPut { key, value } => batch.put(BytesKey::from_u8(key), value),
+                Batch::BatchDel { key } => batch.delete(BytesKey::from_u8(key)),
+            }
+        }
+        let mut options = WriteOptions::new();
+        options.sync = true;
+        self.db.write(options, &batch).map_err(Into::into)
+    }
+    fn iterator(&self, _prefix: &[u8]) -> Result<Box<KvsIterator + 'static>, Error> {
+        Err(Error::from(ErrorKind::Dammy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KvsLevelImpl;
+    use crate::traits::kvs::{Batch, KeyValueStore};
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_reopen() {
+        let dir = TempDir::new("kvs").unwrap();
+        {
+            let mut db = KvsLevelImpl::open_path(dir.path()).unwrap();
+            assert!(db.put(b"key", b"value").is_ok());
+            assert!(db
+                .batch(&[
+                    Batch::BatchPut {
+                        key: b"a",
+                        value: b"1"
+                    },
+                    Batch::BatchDel { key: b"key" },
+                ])
+                .is_ok());
+        }
+        let db = KvsLevelImpl::open_path(dir.path()).unwrap();
+        assert_eq!(db.get(b"key").unwrap(), None);
+        assert_eq!(
+            db.get(b"a").unwrap(),
+            Some(b"1".to_vec().into_boxed_slice())
+        );
+        assert!(db.has(b"a").unwrap());
+    }
+}
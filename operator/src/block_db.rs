@@ -0,0 +1,171 @@
+//
+// Created on Thu Jun 13 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use super::error::Error;
+use ethereum_types::H256;
+use plasma_core::data_structure::{Block, StateUpdate, Transaction};
+use plasma_db::impls::kvs::kvdb::CoreDb;
+use plasma_db::traits::db::DatabaseTrait;
+use plasma_db::traits::kvs::KeyValueStore;
+use rlp::{Rlp, RlpStream};
+
+/// BlockDb persists generated blocks and an index of transactions in them.
+pub struct BlockDb {
+    db: Box<dyn KeyValueStore + Send + Sync>,
+}
+
+impl Default for BlockDb {
+    fn default() -> Self {
+        Self::new(Box::new(CoreDb::open("block")))
+    }
+}
+
+impl BlockDb {
+    const LATEST_KEY: &'static [u8] = b"latest";
+    const BLOCK_PREFIX: &'static [u8] = b"block";
+    const TRANSACTION_PREFIX: &'static [u8] = b"tx";
+    const DEPOSIT_COUNT_KEY: &'static [u8] = b"deposits";
+    const DEPOSIT_PREFIX: &'static [u8] = b"deposit";
+
+    pub fn new(db: Box<dyn KeyValueStore + Send + Sync>) -> Self {
+        BlockDb { db }
+    }
+    fn block_key(number: u64) -> Vec<u8> {
+        let mut key = Self::BLOCK_PREFIX.to_vec();
+        key.extend_from_slice(&number.to_be_bytes());
+        key
+    }
+    fn deposit_key(index: u64) -> Vec<u8> {
+        let mut key = Self::DEPOSIT_PREFIX.to_vec();
+        key.extend_from_slice(&index.to_be_bytes());
+        key
+    }
+    fn get_u64(&self, key: &[u8]) -> Result<u64, Error> {
+        Ok(self.db.get(key)?.map_or(0, |value| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&value);
+            u64::from_be_bytes(bytes)
+        }))
+    }
+    fn transaction_key(hash: &H256) -> Vec<u8> {
+        let mut key = Self::TRANSACTION_PREFIX.to_vec();
+        key.extend_from_slice(hash.as_bytes());
+        key
+    }
+    /// store a block and index its transactions
+    pub fn put_block(&mut self, block: &Block) -> Result<(), Error> {
+        self.db
+            .put(&Self::block_key(block.get_number()), &rlp::encode(block))?;
+        for (index, transaction) in block.get_transactions().iter().enumerate() {
+            let mut location = RlpStream::new_list(2);
+            location.append(&block.get_number());
+            location.append(&(index as u64));
+            self.db
+                .put(&Self::transaction_key(&transaction.hash()), &location.out())?;
+        }
+        self.db
+            .put(Self::LATEST_KEY, &block.get_number().to_be_bytes())
+            .map_err(Into::into)
+    }
+    /// number of the latest block, 0 if there is no block yet
+    pub fn get_block_number(&self) -> Result<u64, Error> {
+        self.get_u64(Self::LATEST_KEY)
+    }
+    pub fn get_block(&self, number: u64) -> Result<Option<Block>, Error> {
+        self.db
+            .get(&Self::block_key(number))?
+            .map(|encoded| rlp::decode(&encoded).map_err(Into::into))
+            .transpose()
+    }
+    pub fn get_latest_block(&self) -> Result<Option<Block>, Error> {
+        self.get_block(self.get_block_number()?)
+    }
+    /// store a deposit with the number of the latest block at that time,
+    /// so that the latest state can be rebuilt in the same order
+    pub fn put_deposit(&mut self, state_update: &StateUpdate) -> Result<(), Error> {
+        let index = self.get_u64(Self::DEPOSIT_COUNT_KEY)?;
+        let mut deposit = RlpStream::new_list(2);
+        deposit.append(&self.get_block_number()?);
+        deposit.append(&state_update.to_abi());
+        self.db.put(&Self::deposit_key(index), &deposit.out())?;
+        self.db
+            .put(Self::DEPOSIT_COUNT_KEY, &(index + 1).to_be_bytes())
+            .map_err(Into::into)
+    }
+    /// deposits with the number of the latest block when they were made
+    pub fn get_deposits(&self) -> Result<Vec<(u64, StateUpdate)>, Error> {
+        (0..self.get_u64(Self::DEPOSIT_COUNT_KEY)?)
+            .filter_map(|index| self.db.get(&Self::deposit_key(index)).transpose())
+            .map(|deposit| -> Result<(u64, StateUpdate), Error> {
+                let deposit = deposit?;
+                let rlp = Rlp::new(&deposit);
+                let block_number: u64 = rlp.val_at(0)?;
+                let encoded: Vec<u8> = rlp.val_at(1)?;
+                Ok((block_number, StateUpdate::from_abi(&encoded)?))
+            })
+            .collect()
+    }
+    /// find a transaction by its hash, and returns the number of the block including it
+    /// and its index in the block
+    pub fn get_transaction_location(&self, hash: &H256) -> Result<Option<(u64, u64)>, Error> {
+        let location = match self.db.get(&Self::transaction_key(hash))? {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+        let rlp = Rlp::new(&location);
+        Ok(Some((rlp.val_at(0)?, rlp.val_at(1)?)))
+    }
+    /// find a transaction by its hash, and returns it with the number of the block including it
+    pub fn get_transaction(&self, hash: &H256) -> Result<Option<(Transaction, u64)>, Error> {
+        let (block_number, index) = match self.get_transaction_location(hash)? {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+        Ok(self.get_block(block_number)?.and_then(|block| {
+            block
+                .get_transactions()
+                .get(index as usize)
+                .map(|transaction| (transaction.clone(), block_number))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockDb;
+    use ethereum_types::{Address, H256};
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::data_structure::{Transaction, Witness};
+    use plasma_core::process::BlockGenerator;
+
+    #[test]
+    fn test_put_and_get_block() {
+        let mut db: BlockDb = Default::default();
+        assert_eq!(db.get_block_number().unwrap(), 0);
+        let transaction = Transaction::new(
+            Address::zero(),
+            0,
+            100,
+            Transaction::create_method_id(&b"send(address)"[..]),
+            &b"parameters"[..],
+            &Witness::new(H256::zero(), H256::zero(), 0),
+        );
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let block =
+            BlockGenerator::generate(&[transaction.clone()], 1, &H256::zero(), 0, &operator_key)
+                .unwrap();
+        assert!(db.put_block(&block).is_ok());
+        assert_eq!(db.get_block_number().unwrap(), 1);
+        assert_eq!(db.get_block(1).unwrap(), Some(block));
+        assert_eq!(db.get_block(2).unwrap(), None);
+        assert_eq!(
+            db.get_transaction(&transaction.hash()).unwrap(),
+            Some((transaction, 1))
+        );
+        assert_eq!(db.get_transaction(&H256::zero()).unwrap(), None);
+    }
+}
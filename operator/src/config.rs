@@ -0,0 +1,229 @@
+//
+// Created on Thu Jun 13 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+/// operator configuration loaded from a TOML file.
+use super::error::{Error, ErrorKind};
+use super::tree_db::Pruning;
+use ethereum_types::Address;
+use plasma_core::data_structure::signature::{SecretKey, Signature};
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+
+/// Configuration of the operator.
+/// Every field is optional in a file, and falls back to its default.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// filter of env_logger such as `info` or `plasma_operator=debug`
+    pub log_level: String,
+    /// address of JSON RPC over HTTP
+    pub address: String,
+    /// the number of HTTP server threads
+    pub threads: usize,
+    /// address of JSON RPC over WebSocket
+    pub ws_address: String,
+    /// address of admin JSON RPC, which shouldn't be exposed to public
+    pub admin_address: String,
+    /// directory to store blocks and states
+    pub db_path: String,
+    /// address of the plasma contract on L1
+    pub plasma_contract: String,
+    /// JSON RPC endpoint of L1
+    pub l1_endpoint: String,
+    /// file containing the hex encoded private key of the operator,
+    /// an ephemeral key is used if it's empty
+    pub operator_key_path: String,
+    /// seconds between blocks
+    pub block_interval: u64,
+    /// a block is generated when this many transactions are pending
+    pub block_max_transactions: usize,
+    /// a block is generated when pending transactions reach this size
+    pub block_max_bytes: usize,
+    /// trees of blocks older than this number of blocks are pruned, 0 keeps every tree
+    pub tree_retention: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            log_level: "info".to_string(),
+            address: "127.0.0.1:8080".to_string(),
+            threads: 3,
+            ws_address: "127.0.0.1:8081".to_string(),
+            admin_address: "127.0.0.1:8082".to_string(),
+            db_path: "./plasma-db".to_string(),
+            plasma_contract: format!("0x{}", hex::encode(Address::zero().as_bytes())),
+            l1_endpoint: "http://127.0.0.1:8545".to_string(),
+            operator_key_path: "".to_string(),
+            block_interval: 10,
+            block_max_transactions: 1000,
+            block_max_bytes: 1024 * 1024,
+            tree_retention: 0,
+        }
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::from(ErrorKind::InvalidConfig(message))
+}
+
+fn validate_socket_addr(name: &str, address: &str) -> Result<(), Error> {
+    address
+        .parse::<SocketAddr>()
+        .map(|_| ())
+        .map_err(|_| invalid(format!("{} `{}` is not a socket address", name, address)))
+}
+
+fn decode_hex(name: &str, value: &str, len: usize) -> Result<Vec<u8>, Error> {
+    hex::decode(value.trim().trim_start_matches("0x"))
+        .ok()
+        .filter(|bytes| bytes.len() == len)
+        .ok_or_else(|| invalid(format!("{} should be {} bytes hex string", name, len)))
+}
+
+impl Config {
+    /// load a TOML file
+    pub fn load(path: &Path) -> Result<Config, Error> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| invalid(format!("can't read {}: {}", path.display(), err)))?;
+        Config::parse(&text)
+    }
+    pub fn parse(text: &str) -> Result<Config, Error> {
+        toml::from_str(text).map_err(|err| invalid(err.to_string()))
+    }
+    pub fn to_toml(&self) -> Result<String, Error> {
+        toml::to_string(self).map_err(|err| invalid(err.to_string()))
+    }
+    /// check values which can be checked without side effects
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_socket_addr("address", &self.address)?;
+        validate_socket_addr("ws_address", &self.ws_address)?;
+        validate_socket_addr("admin_address", &self.admin_address)?;
+        if self.threads == 0 {
+            return Err(invalid("threads should be at least 1".to_string()));
+        }
+        if self.db_path.is_empty() {
+            return Err(invalid("db_path is empty".to_string()));
+        }
+        self.get_plasma_contract()?;
+        if !["http://", "https://", "ws://", "wss://"]
+            .iter()
+            .any(|scheme| self.l1_endpoint.starts_with(scheme))
+        {
+            return Err(invalid(format!(
+                "l1_endpoint `{}` should be an http or ws url",
+                self.l1_endpoint
+            )));
+        }
+        if self.block_interval == 0 {
+            return Err(invalid("block_interval should be at least 1".to_string()));
+        }
+        if self.block_max_transactions == 0 {
+            return Err(invalid(
+                "block_max_transactions should be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+    pub fn get_plasma_contract(&self) -> Result<Address, Error> {
+        decode_hex("plasma_contract", &self.plasma_contract, 20)
+            .map(|bytes| Address::from_slice(&bytes))
+    }
+    /// read the operator's private key from `operator_key_path`
+    pub fn load_operator_key(&self) -> Result<SecretKey, Error> {
+        let text = fs::read_to_string(&self.operator_key_path).map_err(|err| {
+            invalid(format!(
+                "can't read operator key {}: {}",
+                self.operator_key_path, err
+            ))
+        })?;
+        let bytes = decode_hex("operator key", &text, 32)?;
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&bytes);
+        SecretKey::parse(&key).map_err(|_| invalid("operator key is invalid".to_string()))
+    }
+    pub fn get_pruning(&self) -> Pruning {
+        if self.tree_retention == 0 {
+            Pruning::Archive
+        } else {
+            Pruning::KeepLatest(self.tree_retention)
+        }
+    }
+    pub fn get_operator_address(&self) -> Result<Address, Error> {
+        self.load_operator_key()
+            .map(|operator_key| Signature::address_of(&operator_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use crate::tree_db::Pruning;
+    use std::fs;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r#"
+            address = "0.0.0.0:9000"
+            threads = 8
+            block_interval = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.address, "0.0.0.0:9000");
+        assert_eq!(config.threads, 8);
+        assert_eq!(config.block_interval, 3);
+        assert_eq!(config.ws_address, Config::default().ws_address);
+        assert!(config.validate().is_ok());
+        assert_eq!(Config::parse(&config.to_toml().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        assert!(Config::parse("adress = \"0.0.0.0:9000\"").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = Config::default();
+        config.address = "localhost".to_string();
+        assert_eq!(
+            format!("{}", config.validate().unwrap_err()),
+            "Invalid config: address `localhost` is not a socket address"
+        );
+        let mut config = Config::default();
+        config.plasma_contract = "0x1234".to_string();
+        assert!(config.validate().is_err());
+        let mut config = Config::default();
+        config.block_interval = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_get_pruning() {
+        let mut config = Config::default();
+        assert_eq!(config.get_pruning(), Pruning::Archive);
+        config.tree_retention = 100;
+        assert_eq!(config.get_pruning(), Pruning::KeepLatest(100));
+    }
+
+    #[test]
+    fn test_load_operator_key() {
+        let dir = TempDir::new("config").unwrap();
+        let path = dir.path().join("operator_key");
+        fs::write(&path, format!("0x{}\n", "01".repeat(32))).unwrap();
+        let mut config = Config::default();
+        config.operator_key_path = path.to_string_lossy().to_string();
+        assert!(config.load_operator_key().is_ok());
+        fs::write(&path, "0x01").unwrap();
+        assert!(config.load_operator_key().is_err());
+    }
+}
@@ -7,27 +7,891 @@
 
 extern crate plasma_core;
 
-use super::error::Error;
+use super::block_db::BlockDb;
+use super::error::{Error, ErrorKind};
+use super::state_db::StateDb;
+use super::tree_db::{Pruning, TreeDb};
+use super::wal::TransactionLog;
+use ethereum_types::{Address, H256};
+use failure::Fail;
+use log::warn;
 use parking_lot::RwLock;
-use plasma_core::data_structure::{Block, Transaction};
-use plasma_core::process::BlockGenerator;
+use plasma_core::data_structure::error::ErrorKind as PlasmaCoreErrorKind;
+use plasma_core::data_structure::signature::{SecretKey, Signature};
+use plasma_core::data_structure::{
+    Block, InclusionProof, InclusionReceipt, StateUpdate, Transaction,
+};
+use plasma_core::process::{BlockGenerator, StateVerifier};
+use plasma_db::impls::kvs::leveldb::KvsLevelImpl;
+use plasma_rpc::plasmarpc::TransactionStatus;
+use predicate_plugins::PredicateManager;
+use std::cmp::{max, min};
+use std::path::Path;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Default)]
+/// listener of changes on the chain
+pub trait ChainListener {
+    /// called after a transaction is appended to pending transactions
+    fn on_new_transaction(&self, _transaction: &Transaction) {}
+    /// called after a block is generated
+    fn on_new_block(&self, _block: &Block) {}
+    /// called after the latest state of a range is updated
+    fn on_state_update(&self, _state_update: &StateUpdate) {}
+}
+
+/// the maximum number of transactions appended at once
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+#[derive(Clone)]
 pub struct ChainContext {
     transactions: Arc<RwLock<Vec<Transaction>>>,
+    transaction_log: Arc<RwLock<TransactionLog>>,
+    block_db: Arc<RwLock<BlockDb>>,
+    tree_db: Arc<RwLock<TreeDb>>,
+    state_db: Arc<StateDb>,
+    /// the size of pending transactions encoded by ABI, updated with `transactions`
+    pending_bytes: Arc<AtomicUsize>,
+    listeners: Arc<RwLock<Vec<Arc<dyn ChainListener + Send + Sync>>>>,
+    operator_key: SecretKey,
+}
+
+impl Default for ChainContext {
+    /// chain context with an ephemeral operator key
+    fn default() -> Self {
+        Self::new(&SecretKey::random(&mut rand::thread_rng()))
+    }
 }
 
 impl ChainContext {
-    pub fn new() -> Self {
+    /// chain context kept in memory
+    pub fn new(operator_key: &SecretKey) -> Self {
         ChainContext {
             transactions: Arc::new(RwLock::new(vec![])),
+            transaction_log: Arc::new(RwLock::new(Default::default())),
+            block_db: Arc::new(RwLock::new(Default::default())),
+            tree_db: Arc::new(RwLock::new(Default::default())),
+            state_db: Arc::new(Default::default()),
+            pending_bytes: Arc::new(AtomicUsize::new(0)),
+            listeners: Arc::new(RwLock::new(vec![])),
+            operator_key: operator_key.clone(),
+        }
+    }
+    /// open chain context persisted in `path`, and recover it from blocks,
+    /// deposits and pending transactions written before restart.
+    /// Trees of old blocks are pruned by `pruning`.
+    pub fn open(path: &Path, operator_key: &SecretKey, pruning: Pruning) -> Result<Self, Error> {
+        let block_db = BlockDb::new(Box::new(KvsLevelImpl::open_path(&path.join("blocks"))?));
+        let tree_db = TreeDb::new(
+            Box::new(KvsLevelImpl::open_path(&path.join("trees"))?),
+            pruning,
+        );
+        let transaction_log =
+            TransactionLog::new(Box::new(KvsLevelImpl::open_path(&path.join("wal"))?))?;
+        let chain_context = ChainContext {
+            transactions: Arc::new(RwLock::new(vec![])),
+            transaction_log: Arc::new(RwLock::new(transaction_log)),
+            block_db: Arc::new(RwLock::new(block_db)),
+            tree_db: Arc::new(RwLock::new(tree_db)),
+            state_db: Arc::new(Default::default()),
+            pending_bytes: Arc::new(AtomicUsize::new(0)),
+            listeners: Arc::new(RwLock::new(vec![])),
+            operator_key: operator_key.clone(),
+        };
+        chain_context.recover()?;
+        Ok(chain_context)
+    }
+    fn recover(&self) -> Result<(), Error> {
+        let block_db = self.block_db.read();
+        let mut tree_db = self.tree_db.write();
+        let deposits = block_db.get_deposits()?;
+        let mut deposits = deposits.iter().peekable();
+        let latest_block_number = block_db.get_block_number()?;
+        for block_number in 1..=latest_block_number {
+            // deposits made before the block
+            while deposits.peek().map_or(false, |(latest_block_number, _)| {
+                *latest_block_number < block_number
+            }) {
+                if let Some((_, deposit)) = deposits.next() {
+                    self.state_db.put_state_update(deposit)?;
+                }
+            }
+            if let Some(block) = block_db.get_block(block_number)? {
+                for transaction in block.get_transactions().iter() {
+                    self.apply_transaction(block_number, transaction)?;
+                }
+                // the operator may have crashed before the tree was persisted
+                if tree_db.is_retained(block_number, latest_block_number)
+                    && !tree_db.has_tree(block_number)?
+                {
+                    tree_db.put_tree(&block)?;
+                }
+            }
+        }
+        for (_, deposit) in deposits {
+            self.state_db.put_state_update(deposit)?;
+        }
+        // the retention window may be shorter than the last run
+        tree_db.prune(latest_block_number)?;
+        // the operator may have crashed after a block was persisted and before the log was truncated
+        let mut transactions = self.transactions.write();
+        for transaction in self.transaction_log.read().get_transactions()?.iter() {
+            if block_db.get_transaction(&transaction.hash())?.is_none() {
+                transactions.push(transaction.clone());
+                self.pending_bytes
+                    .fetch_add(transaction.to_abi().len(), Ordering::SeqCst);
+            }
         }
+        Ok(())
     }
-    pub fn append(&self, signed_transaction: &Transaction) {
-        self.transactions.write().push(signed_transaction.clone());
+    pub fn get_operator_address(&self) -> Address {
+        Signature::address_of(&self.operator_key)
+    }
+    /// register a listener which is notified of new blocks and state updates
+    pub fn add_listener(&self, listener: Arc<dyn ChainListener + Send + Sync>) {
+        self.listeners.write().push(listener);
+    }
+    /// unregister a listener added by `add_listener`
+    pub fn remove_listener(&self, listener: &Arc<dyn ChainListener + Send + Sync>) {
+        // compare only addresses of data, because vtables of the same type may differ
+        let target = Arc::as_ptr(listener) as *const ();
+        self.listeners
+            .write()
+            .retain(|registered| Arc::as_ptr(registered) as *const () != target);
+    }
+    /// put a state update deposited on L1
+    pub fn deposit(&self, state_update: &StateUpdate) -> Result<(), Error> {
+        let mut block_db = self.block_db.write();
+        block_db.put_deposit(state_update)?;
+        self.state_db.put_state_update(state_update)?;
+        drop(block_db);
+        for listener in self.listeners.read().iter() {
+            listener.on_state_update(state_update);
+        }
+        Ok(())
+    }
+    /// check a transaction against the latest state
+    fn validate(&self, transaction: &Transaction) -> Result<(), Error> {
+        let (start, end) = (transaction.get_start(), transaction.get_end());
+        if start >= end {
+            return Err(Error::from(ErrorKind::InvalidRange { start, end }));
+        }
+        let prior_state_updates = self.state_db.get_state_updates(start, end)?;
+        let mut covered = start;
+        for prior_state_update in prior_state_updates.iter() {
+            if prior_state_update.get_start() > covered {
+                break;
+            }
+            covered = max(covered, prior_state_update.get_end());
+        }
+        if covered < end {
+            let gap_end = prior_state_updates
+                .iter()
+                .map(StateUpdate::get_start)
+                .find(|prior_start| *prior_start > covered)
+                .unwrap_or(end);
+            return Err(Error::from(ErrorKind::NoState {
+                start: covered,
+                end: min(gap_end, end),
+            }));
+        }
+        for prior_state_update in prior_state_updates.iter() {
+            StateVerifier::verify_transition(prior_state_update, transaction)
+                .and_then(|_| {
+                    PredicateManager::get_plugin(
+                        prior_state_update.get_state_object().get_predicate(),
+                    )
+                    .execute_state_transition(prior_state_update, transaction)
+                })
+                .map_err(|err| match err.kind() {
+                    // the transaction isn't signed by the owner
+                    PlasmaCoreErrorKind::InvalidSignature => Error::from(err),
+                    _ => Error::from(ErrorKind::PredicateRejected {
+                        start: prior_state_update.get_start(),
+                        end: prior_state_update.get_end(),
+                        reason: err.to_string(),
+                    }),
+                })?;
+        }
+        Ok(())
+    }
+    /// check a transaction against pending transactions,
+    /// and transactions accepted before it in the same batch
+    fn check_conflict(
+        transaction: &Transaction,
+        pending: &[Transaction],
+        batch: &[Transaction],
+    ) -> Result<(), Error> {
+        let (start, end) = (transaction.get_start(), transaction.get_end());
+        let overlaps = |other: &&Transaction| other.get_start() < end && start < other.get_end();
+        if let Some(conflict) = pending.iter().find(overlaps) {
+            return Err(Error::from(ErrorKind::RangeConflict {
+                start,
+                end,
+                hash: conflict.hash(),
+            }));
+        }
+        if let Some(conflict) = batch.iter().find(overlaps) {
+            return Err(Error::from(ErrorKind::BatchConflict {
+                start,
+                end,
+                hash: conflict.hash(),
+            }));
+        }
+        Ok(())
     }
+    /// validate and append a transaction to pending transactions,
+    /// and returns operator's promise to include it in the next block
+    pub fn append(&self, signed_transaction: &Transaction) -> Result<InclusionReceipt, Error> {
+        // the error of a batch of one transaction isn't wrapped by `BatchRejected`
+        self.append_batch(slice::from_ref(signed_transaction), false)?
+            .pop()
+            .expect("a result for each transaction")
+    }
+    /// validate and append at most `MAX_BATCH_SIZE` transactions,
+    /// and returns a receipt or an error for each of them.
+    /// If `atomic` is true, no transaction is appended unless all of them are valid,
+    /// and the first invalid one is returned as `BatchRejected`.
+    pub fn append_batch(
+        &self,
+        signed_transactions: &[Transaction],
+        atomic: bool,
+    ) -> Result<Vec<Result<InclusionReceipt, Error>>, Error> {
+        if signed_transactions.len() > MAX_BATCH_SIZE {
+            return Err(Error::from(ErrorKind::BatchTooLarge {
+                size: signed_transactions.len(),
+                max: MAX_BATCH_SIZE,
+            }));
+        }
+        // predicates are executed without blocking other appends,
+        // and executed again if a block is generated before the pool is locked
+        let validated_at = self.block_db.read().get_block_number()?;
+        let mut validated: Vec<Result<(), Error>> = signed_transactions
+            .iter()
+            .map(|signed_transaction| self.validate(signed_transaction))
+            .collect();
+        let block_db = self.block_db.read();
+        let mut transactions = self.transactions.write();
+        let latest_block_number = block_db.get_block_number()?;
+        if latest_block_number != validated_at {
+            validated = signed_transactions
+                .iter()
+                .map(|signed_transaction| self.validate(signed_transaction))
+                .collect();
+        }
+        let block_number = latest_block_number + 1;
+        let mut accepted: Vec<Transaction> = vec![];
+        let mut results = vec![];
+        for (index, (signed_transaction, validated)) in
+            signed_transactions.iter().zip(validated).enumerate()
+        {
+            match validated
+                .and_then(|()| Self::check_conflict(signed_transaction, &transactions, &accepted))
+            {
+                Ok(()) => {
+                    accepted.push(signed_transaction.clone());
+                    results.push(Ok(signed_transaction.hash()));
+                }
+                Err(err) if atomic => {
+                    return Err(Error::new(err.context(ErrorKind::BatchRejected { index })));
+                }
+                Err(err) => results.push(Err(err)),
+            }
+        }
+        self.transaction_log.write().append_batch(&accepted)?;
+        transactions.extend(accepted.iter().cloned());
+        self.pending_bytes.fetch_add(
+            accepted
+                .iter()
+                .map(|transaction| transaction.to_abi().len())
+                .sum(),
+            Ordering::SeqCst,
+        );
+        drop(transactions);
+        drop(block_db);
+        for listener in self.listeners.read().iter() {
+            for transaction in accepted.iter() {
+                listener.on_new_transaction(transaction);
+            }
+        }
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                result.and_then(|hash| {
+                    InclusionReceipt::sign(&hash, block_number, &self.operator_key)
+                        .map_err(Into::into)
+                })
+            })
+            .collect())
+    }
+    /// generate a block from pending transactions, and sign its header.
+    /// Every transition is validated and executed before the block is persisted,
+    /// and a transaction which is no longer valid against the latest state is dropped.
+    /// Nothing is changed if the block is not persisted.
     pub fn generate(&self) -> Result<Block, Error> {
-        BlockGenerator::generate(&self.transactions.read().clone()).map_err(Into::into)
+        let mut block_db = self.block_db.write();
+        let mut transactions = self.transactions.write();
+        let block_number = block_db.get_block_number()? + 1;
+        let mut included = vec![];
+        let mut state_updates = vec![];
+        for transaction in transactions.iter() {
+            match self
+                .validate(transaction)
+                .and_then(|()| self.execute_transaction(block_number, transaction))
+            {
+                Ok(executed) => {
+                    included.push(transaction.clone());
+                    state_updates.extend(executed);
+                }
+                Err(err) => warn!("dropped transaction {:?}: {}", transaction.hash(), err),
+            }
+        }
+        let prev_hash = block_db
+            .get_latest_block()?
+            .map_or_else(H256::zero, |block| block.hash());
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let (block, tree) = BlockGenerator::generate_with_tree(
+            &included,
+            block_number,
+            &prev_hash,
+            timestamp,
+            &self.operator_key,
+        )?;
+        block_db.put_block(&block)?;
+        // the block is committed. Failures from here are repaired by `recover`,
+        // which skips logged transactions included in blocks and rebuilds missing trees.
+        transactions.clear();
+        self.pending_bytes.store(0, Ordering::SeqCst);
+        self.put_state_updates(&state_updates)?;
+        if let Err(err) = self.transaction_log.write().truncate() {
+            warn!("failed to truncate the transaction log: {}", err);
+        }
+        if let Err(err) = self.tree_db.write().put_tree_of(&block, tree.as_ref()) {
+            warn!(
+                "failed to persist the tree of block {}: {}",
+                block_number, err
+            );
+        }
+        drop(transactions);
+        drop(block_db);
+        for listener in self.listeners.read().iter() {
+            listener.on_new_block(&block);
+        }
+        Ok(block)
+    }
+    /// compute state updates made by a transaction against the latest state
+    fn execute_transaction(
+        &self,
+        block_number: u64,
+        transaction: &Transaction,
+    ) -> Result<Vec<StateUpdate>, Error> {
+        let prior_state_updates = self
+            .state_db
+            .get_state_updates(transaction.get_start(), transaction.get_end())?;
+        prior_state_updates
+            .iter()
+            .map(|prior_state_update| {
+                let new_state_update = PredicateManager::get_plugin(
+                    prior_state_update.get_state_object().get_predicate(),
+                )
+                .execute_state_transition(prior_state_update, transaction)?;
+                Ok(StateUpdate::new(
+                    new_state_update.get_state_object(),
+                    max(prior_state_update.get_start(), transaction.get_start()),
+                    min(prior_state_update.get_end(), transaction.get_end()),
+                    block_number,
+                    *new_state_update.get_plasma_contract(),
+                ))
+            })
+            .collect()
+    }
+    /// update the latest state updates by a transaction
+    fn apply_transaction(&self, block_number: u64, transaction: &Transaction) -> Result<(), Error> {
+        self.put_state_updates(&self.execute_transaction(block_number, transaction)?)
+    }
+    fn put_state_updates(&self, state_updates: &[StateUpdate]) -> Result<(), Error> {
+        for state_update in state_updates.iter() {
+            self.state_db.put_state_update(state_update)?;
+            for listener in self.listeners.read().iter() {
+                listener.on_state_update(state_update);
+            }
+        }
+        Ok(())
+    }
+    /// validate a transaction and returns state updates it would make in the next block,
+    /// without appending it
+    pub fn simulate(&self, transaction: &Transaction) -> Result<Vec<StateUpdate>, Error> {
+        let block_db = self.block_db.read();
+        self.validate(transaction)?;
+        Self::check_conflict(transaction, &self.transactions.read(), &[])?;
+        self.execute_transaction(block_db.get_block_number()? + 1, transaction)
+    }
+    pub fn get_pending_transaction_count(&self) -> usize {
+        self.transactions.read().len()
+    }
+    /// the size of pending transactions encoded by ABI
+    pub fn get_pending_transaction_bytes(&self) -> usize {
+        self.pending_bytes.load(Ordering::SeqCst)
+    }
+    pub fn get_block_number(&self) -> Result<u64, Error> {
+        self.block_db.read().get_block_number()
+    }
+    pub fn get_block(&self, number: u64) -> Result<Option<Block>, Error> {
+        self.block_db.read().get_block(number)
+    }
+    /// find a transaction in blocks or pending transactions
+    pub fn get_transaction(&self, hash: &H256) -> Result<Option<Transaction>, Error> {
+        if let Some((transaction, _)) = self.block_db.read().get_transaction(hash)? {
+            return Ok(Some(transaction));
+        }
+        Ok(self
+            .transactions
+            .read()
+            .iter()
+            .find(|transaction| &transaction.hash() == hash)
+            .cloned())
+    }
+    pub fn get_transaction_status(&self, hash: &H256) -> Result<TransactionStatus, Error> {
+        if let Some((_, block_number)) = self.block_db.read().get_transaction(hash)? {
+            return Ok(TransactionStatus::Included { block_number });
+        }
+        let is_pending = self
+            .transactions
+            .read()
+            .iter()
+            .any(|transaction| &transaction.hash() == hash);
+        Ok(if is_pending {
+            TransactionStatus::Pending
+        } else {
+            TransactionStatus::Unknown
+        })
+    }
+    /// find an inclusion proof of a transaction, and returns it with the number of the block
+    /// including the transaction. The proof is read from the stored tree, or computed from
+    /// the block if its tree is pruned.
+    pub fn get_inclusion_proof(&self, hash: &H256) -> Result<Option<(u64, InclusionProof)>, Error> {
+        let block_db = self.block_db.read();
+        let (block_number, index) = match block_db.get_transaction_location(hash)? {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+        if let Some(inclusion_proof) = self
+            .tree_db
+            .read()
+            .get_inclusion_proof(block_number, index as usize)?
+        {
+            return Ok(Some((block_number, inclusion_proof)));
+        }
+        Ok(block_db
+            .get_block(block_number)?
+            .and_then(|block| block.get_inclusion_proof(index as usize))
+            .map(|inclusion_proof| (block_number, inclusion_proof)))
+    }
+    /// get the latest state updates between start and end
+    pub fn get_state_updates(&self, start: u64, end: u64) -> Result<Vec<StateUpdate>, Error> {
+        self.state_db.get_state_updates(start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChainContext, ChainListener, TransactionStatus, MAX_BATCH_SIZE};
+    use crate::error::ErrorKind;
+    use crate::rpc::errors::{from_error, ApplicationError};
+    use crate::test_utils::{
+        create_deposit, create_state_update, create_transaction, create_transaction_signed_by,
+    };
+    use crate::tree_db::Pruning;
+    use ethereum_types::{Address, H256};
+    use parking_lot::RwLock;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::data_structure::{Block, StateObject, StateUpdate, Transaction, Witness};
+    use plasma_core::process::{BlockGenerator, HeaderVerifier};
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    #[derive(Default)]
+    struct EventRecorder {
+        blocks: RwLock<Vec<u64>>,
+        state_updates: RwLock<Vec<(u64, u64)>>,
+    }
+
+    impl ChainListener for EventRecorder {
+        fn on_new_block(&self, block: &Block) {
+            self.blocks.write().push(block.get_number());
+        }
+        fn on_state_update(&self, state_update: &StateUpdate) {
+            self.state_updates
+                .write()
+                .push((state_update.get_start(), state_update.get_end()));
+        }
+    }
+
+    #[test]
+    fn test_generate_chain_of_blocks() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        assert!(chain_context.append(&create_transaction(0, 100)).is_ok());
+        let block1 = chain_context.generate().unwrap();
+        assert!(chain_context.append(&create_transaction(100, 200)).is_ok());
+        let block2 = chain_context.generate().unwrap();
+        assert_eq!(block1.get_number(), 1);
+        assert_eq!(block2.get_number(), 2);
+        assert_eq!(block2.get_transactions().len(), 1);
+        let operator = chain_context.get_operator_address();
+        assert!(HeaderVerifier::verify_block(&block2, &operator).is_ok());
+        assert!(HeaderVerifier::verify_chain(
+            &[block1.get_header().clone(), block2.get_header().clone()],
+            &operator
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_transaction_status() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 100)).is_ok());
+        let transaction = create_transaction(0, 100);
+        assert_eq!(
+            chain_context
+                .get_transaction_status(&transaction.hash())
+                .unwrap(),
+            TransactionStatus::Unknown
+        );
+        assert!(chain_context.append(&transaction).is_ok());
+        assert_eq!(
+            chain_context
+                .get_transaction_status(&transaction.hash())
+                .unwrap(),
+            TransactionStatus::Pending
+        );
+        assert!(chain_context.generate().is_ok());
+        assert_eq!(
+            chain_context
+                .get_transaction_status(&transaction.hash())
+                .unwrap(),
+            TransactionStatus::Included { block_number: 1 }
+        );
+        assert_eq!(
+            chain_context.get_transaction(&transaction.hash()).unwrap(),
+            Some(transaction)
+        );
+    }
+
+    #[test]
+    fn test_state_updates() {
+        let chain_context: ChainContext = Default::default();
+        let deposit = create_deposit(0, 100);
+        assert!(chain_context.deposit(&deposit).is_ok());
+        assert!(chain_context.append(&create_transaction(0, 50)).is_ok());
+        assert!(chain_context.generate().is_ok());
+        let state_updates = chain_context.get_state_updates(0, 100).unwrap();
+        assert_eq!(state_updates.len(), 2);
+        assert_eq!(state_updates[0].get_end(), 50);
+        assert_eq!(state_updates[0].get_block_number(), 1);
+        assert_eq!(state_updates[1].get_start(), 50);
+        assert_eq!(state_updates[1].get_block_number(), 0);
+    }
+
+    #[test]
+    fn test_listener() {
+        let chain_context: ChainContext = Default::default();
+        let recorder = Arc::new(EventRecorder::default());
+        chain_context.add_listener(recorder.clone());
+        let deposit = create_deposit(0, 100);
+        assert!(chain_context.deposit(&deposit).is_ok());
+        assert!(chain_context.append(&create_transaction(20, 50)).is_ok());
+        assert!(chain_context.generate().is_ok());
+        assert_eq!(*recorder.blocks.read(), vec![1]);
+        assert_eq!(*recorder.state_updates.read(), vec![(0, 100), (20, 50)]);
+        let listener: Arc<dyn ChainListener + Send + Sync> = recorder.clone();
+        chain_context.remove_listener(&listener);
+        assert!(chain_context.generate().is_ok());
+        assert_eq!(*recorder.blocks.read(), vec![1]);
+    }
+
+    #[test]
+    fn test_pending_transaction_bytes() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 300)).is_ok());
+        let transaction = create_transaction(0, 100);
+        let batch = vec![create_transaction(100, 200), create_transaction(200, 300)];
+        assert!(chain_context.append(&transaction).is_ok());
+        assert!(chain_context.append_batch(&batch, true).is_ok());
+        assert_eq!(
+            chain_context.get_pending_transaction_bytes(),
+            transaction.to_abi().len() * 3
+        );
+        assert!(chain_context.generate().is_ok());
+        assert_eq!(chain_context.get_pending_transaction_bytes(), 0);
+    }
+
+    #[test]
+    fn test_drop_transaction_rejected_while_generating() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 100)).is_ok());
+        let rejected = create_transaction(0, 50);
+        let included = create_transaction(50, 100);
+        assert!(chain_context.append(&rejected).is_ok());
+        assert!(chain_context.append(&included).is_ok());
+        // the range is moved to another plasma contract after the transaction was accepted
+        let moved = StateUpdate::new(
+            &StateObject::new(Address::zero(), &b"data"[..]),
+            0,
+            50,
+            0,
+            Address::from_low_u64_be(1),
+        );
+        assert!(chain_context.deposit(&moved).is_ok());
+        let block = chain_context.generate().unwrap();
+        assert_eq!(block.get_transactions(), &[included.clone()][..]);
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+        assert_eq!(
+            chain_context
+                .get_transaction_status(&rejected.hash())
+                .unwrap(),
+            TransactionStatus::Unknown
+        );
+        assert_eq!(
+            chain_context.get_state_updates(0, 100).unwrap(),
+            vec![moved, create_state_update(50, 100, 1)]
+        );
+        // the next block doesn't include the transactions again
+        assert!(chain_context
+            .generate()
+            .unwrap()
+            .get_transactions()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_recover_after_restart() {
+        let dir = TempDir::new("context").unwrap();
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let deposit = create_deposit(0, 100);
+        {
+            let chain_context =
+                ChainContext::open(dir.path(), &operator_key, Pruning::Archive).unwrap();
+            assert!(chain_context.deposit(&deposit).is_ok());
+            assert!(chain_context.append(&create_transaction(0, 50)).is_ok());
+            assert!(chain_context.generate().is_ok());
+            // killed while the next block is pending
+            assert!(chain_context.append(&create_transaction(50, 80)).is_ok());
+        }
+        let chain_context =
+            ChainContext::open(dir.path(), &operator_key, Pruning::Archive).unwrap();
+        assert_eq!(chain_context.get_block_number().unwrap(), 1);
+        assert_eq!(chain_context.get_pending_transaction_count(), 1);
+        let state_updates = chain_context.get_state_updates(0, 100).unwrap();
+        assert_eq!(state_updates.len(), 2);
+        assert_eq!(state_updates[0].get_block_number(), 1);
+        let block = chain_context.generate().unwrap();
+        assert_eq!(block.get_number(), 2);
+        assert_eq!(block.get_transactions(), &[create_transaction(50, 80)][..]);
+        assert_eq!(
+            block.get_header().get_prev_hash(),
+            &chain_context.get_block(1).unwrap().unwrap().hash()
+        );
+    }
+
+    #[test]
+    fn test_recover_included_transactions() {
+        let dir = TempDir::new("context").unwrap();
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let transaction = create_transaction(0, 50);
+        {
+            let chain_context =
+                ChainContext::open(dir.path(), &operator_key, Pruning::Archive).unwrap();
+            assert!(chain_context.deposit(&create_deposit(0, 100)).is_ok());
+            assert!(chain_context.append(&transaction).is_ok());
+            // killed after the block is persisted and before the log is truncated
+            let block = BlockGenerator::generate(
+                &[transaction.clone()],
+                1,
+                &H256::zero(),
+                0,
+                &operator_key,
+            )
+            .unwrap();
+            assert!(chain_context.block_db.write().put_block(&block).is_ok());
+        }
+        let chain_context =
+            ChainContext::open(dir.path(), &operator_key, Pruning::Archive).unwrap();
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+        assert_eq!(
+            chain_context
+                .get_transaction_status(&transaction.hash())
+                .unwrap(),
+            TransactionStatus::Included { block_number: 1 }
+        );
+        // the tree is persisted while recovering
+        assert!(chain_context.tree_db.read().has_tree(1).unwrap());
+        let (block_number, inclusion_proof) = chain_context
+            .get_inclusion_proof(&transaction.hash())
+            .unwrap()
+            .unwrap();
+        assert_eq!(block_number, 1);
+        assert_eq!(inclusion_proof.get_leaf_index(), 0);
+    }
+
+    #[test]
+    fn test_get_inclusion_proof() {
+        let dir = TempDir::new("context").unwrap();
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let chain_context =
+            ChainContext::open(dir.path(), &operator_key, Pruning::KeepLatest(1)).unwrap();
+        assert!(chain_context.deposit(&create_deposit(0, 100)).is_ok());
+        let transactions = vec![create_transaction(0, 50), create_transaction(50, 100)];
+        for transaction in transactions.iter() {
+            assert!(chain_context.append(transaction).is_ok());
+            assert!(chain_context.generate().is_ok());
+        }
+        // the tree of the first block is pruned, and the proof is computed from the block
+        assert!(!chain_context.tree_db.read().has_tree(1).unwrap());
+        for (index, transaction) in transactions.iter().enumerate() {
+            let block_number = index as u64 + 1;
+            let block = chain_context.get_block(block_number).unwrap().unwrap();
+            assert_eq!(
+                chain_context
+                    .get_inclusion_proof(&transaction.hash())
+                    .unwrap(),
+                Some((block_number, block.get_inclusion_proof(0).unwrap()))
+            );
+        }
+        assert_eq!(
+            chain_context.get_inclusion_proof(&H256::zero()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reject_invalid_transactions() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 100)).is_ok());
+        assert!(chain_context.deposit(&create_deposit(150, 200)).is_ok());
+        assert_eq!(
+            format!(
+                "{}",
+                chain_context
+                    .append(&create_transaction(50, 180))
+                    .unwrap_err()
+            ),
+            "Invalid transaction: range [100, 150) has no state"
+        );
+        let other_contract = Transaction::new(
+            Address::from_low_u64_be(1),
+            0,
+            100,
+            Transaction::create_method_id(&b"send(address)"[..]),
+            &b"parameters"[..],
+            &Witness::new(H256::zero(), H256::zero(), 0),
+        );
+        assert!(chain_context.append(&other_contract).is_err());
+        assert!(chain_context.append(&create_transaction(0, 50)).is_ok());
+        assert!(chain_context.append(&create_transaction(40, 60)).is_err());
+        assert!(chain_context.append(&create_transaction(100, 100)).is_err());
+        assert_eq!(chain_context.get_pending_transaction_count(), 1);
+    }
+
+    #[test]
+    fn test_reject_transaction_of_non_owner() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 100)).is_ok());
+        let other_key = SecretKey::parse(&[2u8; 32]).unwrap();
+        let error = chain_context
+            .append(&create_transaction_signed_by(&other_key, 0, 50))
+            .unwrap_err();
+        assert_eq!(
+            from_error(error).code,
+            ApplicationError::InvalidSignature.code()
+        );
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+        assert!(chain_context.append(&create_transaction(0, 50)).is_ok());
+    }
+
+    #[test]
+    fn test_append_batch() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 300)).is_ok());
+        let batch = vec![
+            create_transaction(0, 100),
+            create_transaction(50, 150),
+            create_transaction(200, 300),
+        ];
+        let error = chain_context.append_batch(&batch, true).unwrap_err();
+        match error.kind() {
+            ErrorKind::BatchRejected { index } => assert_eq!(*index, 1),
+            _ => panic!("batch should be rejected"),
+        }
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+        let results = chain_context.append_batch(&batch, false).unwrap();
+        assert!(results[0].is_ok());
+        match results[1].as_ref().unwrap_err().kind() {
+            ErrorKind::BatchConflict { hash, .. } => assert_eq!(*hash, batch[0].hash()),
+            _ => panic!("transaction should conflict with the batch"),
+        }
+        assert_eq!(
+            results[2].as_ref().unwrap().get_transaction_hash(),
+            &batch[2].hash()
+        );
+        assert_eq!(chain_context.get_pending_transaction_count(), 2);
+        match chain_context
+            .append(&create_transaction(50, 150))
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::RangeConflict { hash, .. } => assert_eq!(*hash, batch[0].hash()),
+            _ => panic!("transaction should conflict with the pending transaction"),
+        }
+    }
+
+    #[test]
+    fn test_batch_size_limit() {
+        let chain_context: ChainContext = Default::default();
+        let batch: Vec<Transaction> = (0..=MAX_BATCH_SIZE as u64)
+            .map(|i| create_transaction(i, i + 1))
+            .collect();
+        match chain_context
+            .append_batch(&batch, false)
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::BatchTooLarge { size, .. } => assert_eq!(*size, MAX_BATCH_SIZE + 1),
+            _ => panic!("batch should be too large"),
+        }
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+    }
+
+    #[test]
+    fn test_simulate() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 100)).is_ok());
+        assert!(chain_context.deposit(&create_deposit(100, 200)).is_ok());
+        let state_updates = chain_context
+            .simulate(&create_transaction(50, 150))
+            .unwrap();
+        assert_eq!(state_updates.len(), 2);
+        assert_eq!(
+            (state_updates[0].get_start(), state_updates[0].get_end()),
+            (50, 100)
+        );
+        assert_eq!(
+            (state_updates[1].get_start(), state_updates[1].get_end()),
+            (100, 150)
+        );
+        assert_eq!(state_updates[1].get_block_number(), 1);
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+        assert_eq!(
+            chain_context.get_state_updates(0, 200).unwrap(),
+            vec![create_deposit(0, 100), create_deposit(100, 200)]
+        );
+        assert!(chain_context
+            .simulate(&create_transaction(150, 250))
+            .is_err());
     }
 }
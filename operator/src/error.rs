@@ -6,8 +6,12 @@
 //
 
 /// error definition for plasma chain.
+use ethereum_types::H256;
 use failure::{Backtrace, Context, Fail};
+use jsonrpc_ws_server::Error as WsError;
 use plasma_core::data_structure::error::Error as PlasmaCoreError;
+use plasma_db::error::Error as PlasmaDbError;
+use rlp::DecoderError;
 use std::fmt;
 use std::fmt::Display;
 use std::io::Error as IoError;
@@ -21,6 +25,49 @@ pub enum ErrorKind {
     Parse,
     #[fail(display = "Plasma Core")]
     PlasmaCore,
+    #[fail(display = "Plasma Db")]
+    PlasmaDb,
+    #[fail(display = "RLP Decode error")]
+    RlpDecode,
+    #[fail(display = "WebSocket error")]
+    WebSocket,
+    #[fail(display = "Invalid config: {}", _0)]
+    InvalidConfig(String),
+    #[fail(display = "Invalid transaction: range [{}, {}) is empty", start, end)]
+    InvalidRange { start: u64, end: u64 },
+    #[fail(
+        display = "Invalid transaction: range [{}, {}) conflicts with pending transaction {:?}",
+        start, end, hash
+    )]
+    RangeConflict { start: u64, end: u64, hash: H256 },
+    #[fail(
+        display = "Invalid transaction: range [{}, {}) conflicts with transaction {:?} of the same batch",
+        start, end, hash
+    )]
+    BatchConflict { start: u64, end: u64, hash: H256 },
+    #[fail(
+        display = "Invalid transaction: range [{}, {}) has no state",
+        start, end
+    )]
+    NoState { start: u64, end: u64 },
+    #[fail(
+        display = "Invalid transaction: state update of [{}, {}) can't be spent: {}",
+        start, end, reason
+    )]
+    PredicateRejected {
+        start: u64,
+        end: u64,
+        reason: String,
+    },
+    #[fail(display = "Transaction {} of the batch is rejected", index)]
+    BatchRejected { index: usize },
+    #[fail(
+        display = "Batch of {} transactions exceeds the limit of {}",
+        size, max
+    )]
+    BatchTooLarge { size: usize, max: usize },
+    #[fail(display = "A node of the tree of block {} is missing", block_number)]
+    MissingTreeNode { block_number: u64 },
 }
 
 #[derive(Debug)]
@@ -91,3 +138,27 @@ impl From<PlasmaCoreError> for Error {
         }
     }
 }
+
+impl From<PlasmaDbError> for Error {
+    fn from(error: PlasmaDbError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::PlasmaDb),
+        }
+    }
+}
+
+impl From<DecoderError> for Error {
+    fn from(error: DecoderError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::RlpDecode),
+        }
+    }
+}
+
+impl From<WsError> for Error {
+    fn from(error: WsError) -> Error {
+        Error {
+            inner: error.context(ErrorKind::WebSocket),
+        }
+    }
+}
@@ -9,24 +9,109 @@ extern crate failure;
 extern crate serde;
 extern crate serde_derive;
 
+/// persisted blocks
+pub mod block_db;
+/// operator configuration.
+pub mod config;
 /// plasma chain context data structure
 pub mod context;
 /// error definitions.
 pub mod error;
 /// APIs for JSON RPC.
 pub mod rpc;
+/// block production scheduler.
+pub mod scheduler;
 /// Plasma JSON RPC server.
 pub mod server;
+/// the latest state updates of the chain
+pub mod state_db;
+/// fixtures shared by tests
+#[cfg(test)]
+pub mod test_utils;
+/// persisted sum merkle trees of blocks
+pub mod tree_db;
+/// write-ahead log of pending transactions
+pub mod wal;
 
-use self::server::get_server;
-use env_logger;
+use self::config::Config;
+use self::context::ChainContext;
+use self::error::{Error, ErrorKind};
+use self::rpc::auth::generate_token_file;
+use self::scheduler::{Scheduler, SchedulerOption};
+use self::server::{
+    get_admin_server, get_server, get_ws_server, AdminOption, HttpOption, WsOption,
+};
+use failure::Fail;
+use log::{info, warn};
+use plasma_core::data_structure::signature::SecretKey;
 use std::env;
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::time::Duration;
 
 /// entry point of plasma chain.
-pub fn entry() {
-    env::set_var("RUST_LOG", "info");
-    env_logger::init();
-    println!("Hello, operator!!");
-    let server = get_server(&Default::default()).expect("Unable to start rpc server.");
-    server.wait();
+/// It runs until SIGINT or SIGTERM, and then stops servers and the scheduler.
+pub fn entry(config: &Config) -> Result<(), Error> {
+    config.validate()?;
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(&config.log_level))
+        .init();
+    let operator_key = if config.operator_key_path.is_empty() {
+        warn!("operator_key_path is not set, using an ephemeral operator key");
+        SecretKey::random(&mut rand::thread_rng())
+    } else {
+        config.load_operator_key()?
+    };
+    let chain_context = ChainContext::open(
+        Path::new(&config.db_path),
+        &operator_key,
+        config.get_pruning(),
+    )?;
+    info!(
+        "operator 0x{} for plasma contract {} on {}",
+        hex::encode(chain_context.get_operator_address().as_bytes()),
+        config.plasma_contract,
+        config.l1_endpoint
+    );
+    let server = get_server(
+        &HttpOption::new(&config.address, config.threads),
+        &chain_context,
+    )?;
+    let ws_server = get_ws_server(&WsOption::new(&config.ws_address), &chain_context)?;
+    let admin_token = match env::var("PLASMA_ADMIN_TOKEN") {
+        Ok(token) => token,
+        Err(_) => {
+            let path = Path::new(&config.db_path).join("admin_token");
+            let token = generate_token_file(&path)?;
+            info!(
+                "PLASMA_ADMIN_TOKEN is not set, generated admin token is written to {}",
+                path.display()
+            );
+            token
+        }
+    };
+    let admin_server = get_admin_server(
+        &AdminOption::new(&config.admin_address, &admin_token),
+        &chain_context,
+    )?;
+    let scheduler = Scheduler::start(
+        SchedulerOption::new(
+            Duration::from_secs(config.block_interval),
+            config.block_max_transactions,
+            config.block_max_bytes,
+        ),
+        &chain_context,
+    );
+    let (sender, receiver) = channel();
+    ctrlc::set_handler(move || {
+        let _ = sender.send(());
+    })
+    .map_err(|err| Error::from(err.context(ErrorKind::Io)))?;
+    let _ = receiver.recv();
+    info!("shutting down");
+    server.close();
+    ws_server.close();
+    admin_server.close();
+    // waits for the block being generated
+    scheduler.shutdown();
+    Ok(())
 }
@@ -5,7 +5,14 @@
 // This file is part of Plasma Chamber.
 //
 
+/// APIs for the operator.
+pub mod adminrpc;
+pub mod adminrpcimpl;
+/// authentication of admin APIs
+pub mod auth;
 /// error definitions for json-rpc
 pub mod errors;
-pub mod plasmarpc;
 pub mod plasmarpcimpl;
+/// subscriptions over WebSocket
+pub mod pubsub;
+pub mod pubsubimpl;
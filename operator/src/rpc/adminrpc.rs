@@ -0,0 +1,30 @@
+//
+// Created on Tue Jun 11 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+extern crate jsonrpc_core;
+extern crate jsonrpc_derive;
+
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+/// Plasma JSON RPC trait for the operator.
+/// This should be served only on the admin address.
+#[rpc]
+pub trait AdminRpc {
+    /// generate a block from pending transactions, and returns it encoded by RLP
+    #[rpc(name = "generateBlock")]
+    fn generate_block(&self) -> Result<String>;
+    /// put a state update deposited on L1 encoded by ABI
+    #[rpc(name = "deposit")]
+    fn deposit(&self, message: String) -> Result<bool>;
+    /// Returns the address of the operator
+    #[rpc(name = "getOperatorAddress")]
+    fn get_operator_address(&self) -> Result<String>;
+    /// Returns the number of pending transactions
+    #[rpc(name = "getPendingTransactionCount")]
+    fn get_pending_transaction_count(&self) -> Result<usize>;
+}
@@ -0,0 +1,116 @@
+//
+// Created on Tue Jun 11 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+extern crate jsonrpc_core;
+extern crate plasma_core;
+extern crate rlp;
+
+use super::adminrpc::AdminRpc;
+use super::errors;
+use crate::context::ChainContext;
+use jsonrpc_core::Result;
+use plasma_core::data_structure::StateUpdate;
+
+/// Plasma JSON RPC implementation for the operator.
+#[derive(Default)]
+pub struct AdminRpcImpl {
+    chain_context: ChainContext,
+}
+
+impl AdminRpcImpl {
+    pub fn from_chain_context(chain_context: ChainContext) -> AdminRpcImpl {
+        AdminRpcImpl { chain_context }
+    }
+}
+
+impl AdminRpc for AdminRpcImpl {
+    fn generate_block(&self) -> Result<String> {
+        self.chain_context
+            .generate()
+            .map(|block| rlp::encode(&block))
+            .map(hex::encode)
+            .map_err(errors::from_error)
+    }
+    fn deposit(&self, message: String) -> Result<bool> {
+        let abi_bytes =
+            hex::decode(message).map_err(|err| errors::decode_failure("state update", err))?;
+        let state_update = StateUpdate::from_abi(&abi_bytes)
+            .map_err(|err| errors::decode_failure("state update", err))?;
+        self.chain_context
+            .deposit(&state_update)
+            .map(|_| true)
+            .map_err(errors::from_error)
+    }
+    fn get_operator_address(&self) -> Result<String> {
+        Ok(format!(
+            "0x{}",
+            hex::encode(self.chain_context.get_operator_address().as_bytes())
+        ))
+    }
+    fn get_pending_transaction_count(&self) -> Result<usize> {
+        Ok(self.chain_context.get_pending_transaction_count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdminRpc;
+    use super::AdminRpcImpl;
+    use crate::context::ChainContext;
+    use crate::test_utils::{create_deposit, create_transaction};
+    use jsonrpc_http_server::jsonrpc_core::IoHandler;
+    use plasma_core::data_structure::signature::SecretKey;
+
+    #[test]
+    fn test_generate_block() {
+        let mut io = IoHandler::new();
+
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let chain_context = ChainContext::new(&operator_key);
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let transaction = create_transaction(0, 100);
+        assert!(chain_context.append(&transaction).is_ok());
+        let rpc = AdminRpcImpl::from_chain_context(chain_context.clone());
+        io.extend_with(rpc.to_delegate());
+
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "getPendingTransactionCount", "params": [], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","result":1,"id":1}"#;
+        assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+        let request = r#"{"jsonrpc": "2.0", "method": "generateBlock", "params": [], "id": 1}"#;
+        let result = io.handle_request_sync(request).unwrap();
+        let block = chain_context.get_block(1).unwrap().unwrap();
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#,
+            hex::encode(rlp::encode(&block))
+        );
+        assert_eq!(result, response);
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+    }
+
+    #[test]
+    fn test_deposit() {
+        let mut io = IoHandler::new();
+
+        let chain_context: ChainContext = Default::default();
+        let rpc = AdminRpcImpl::from_chain_context(chain_context.clone());
+        io.extend_with(rpc.to_delegate());
+
+        let deposit = create_deposit(0, 100);
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "deposit", "params": ["{}"], "id": 1}}"#,
+            hex::encode(deposit.to_abi())
+        );
+        let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+        assert_eq!(io.handle_request_sync(&request), Some(response.to_string()));
+        assert_eq!(
+            chain_context.get_state_updates(0, 100).unwrap(),
+            vec![deposit]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//
+// Created on Tue Jun 11 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use crate::error::Error;
+use jsonrpc_http_server::hyper::header::{HeaderValue, AUTHORIZATION};
+use jsonrpc_http_server::hyper::{Body, Request, StatusCode};
+use jsonrpc_http_server::{RequestMiddleware, RequestMiddlewareAction, Response};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+
+/// generate a random token, and write it to `path` which only its owner can read
+pub fn generate_token_file(path: &Path) -> Result<String, Error> {
+    let token: [u8; 32] = rand::random();
+    let token = hex::encode(token);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    // the mode is applied only when the file is created
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(token.as_bytes())?;
+    Ok(token)
+}
+
+/// Rejects requests without `Authorization: Bearer <token>` header.
+pub struct TokenAuth {
+    expected: Vec<u8>,
+}
+
+impl TokenAuth {
+    pub fn new(token: &str) -> Self {
+        TokenAuth {
+            expected: format!("Bearer {}", token).into_bytes(),
+        }
+    }
+    /// compares in constant time not to leak the token by timing
+    pub fn is_authorized(&self, header: Option<&HeaderValue>) -> bool {
+        header.map_or(false, |value| {
+            let value = value.as_bytes();
+            value.len() == self.expected.len()
+                && value
+                    .iter()
+                    .zip(self.expected.iter())
+                    .fold(0, |acc, (a, b)| acc | (a ^ b))
+                    == 0
+        })
+    }
+}
+
+impl RequestMiddleware for TokenAuth {
+    fn on_request(&self, request: Request<Body>) -> RequestMiddlewareAction {
+        if self.is_authorized(request.headers().get(AUTHORIZATION)) {
+            RequestMiddlewareAction::Proceed {
+                should_continue_on_invalid_cors: false,
+                request,
+            }
+        } else {
+            Response {
+                code: StatusCode::UNAUTHORIZED,
+                content_type: HeaderValue::from_static("text/plain; charset=utf-8"),
+                content: "Unauthorized\n".to_string(),
+            }
+            .into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_token_file, TokenAuth};
+    use jsonrpc_http_server::hyper::header::AUTHORIZATION;
+    use jsonrpc_http_server::hyper::{Body, Request};
+    use jsonrpc_http_server::{RequestMiddleware, RequestMiddlewareAction};
+    use std::fs;
+    use tempdir::TempDir;
+
+    fn request(authorization: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder();
+        builder.method("POST").uri("/");
+        if let Some(authorization) = authorization {
+            builder.header(AUTHORIZATION, authorization);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_token_auth() {
+        let auth = TokenAuth::new("secret");
+        match auth.on_request(request(Some("Bearer secret"))) {
+            RequestMiddlewareAction::Proceed { .. } => {}
+            _ => panic!("authorized request should proceed"),
+        }
+        for authorization in [None, Some("Bearer secres"), Some("secret")].iter() {
+            match auth.on_request(request(*authorization)) {
+                RequestMiddlewareAction::Respond { .. } => {}
+                _ => panic!("unauthorized request should be rejected"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_token_file() {
+        let dir = TempDir::new("auth").unwrap();
+        let path = dir.path().join("db").join("admin_token");
+        let token = generate_token_file(&path).unwrap();
+        assert_eq!(token.len(), 64);
+        assert_eq!(fs::read_to_string(&path).unwrap(), token);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_ne!(generate_token_file(&path).unwrap(), token);
+    }
+}
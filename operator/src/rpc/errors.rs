@@ -5,9 +5,181 @@
 // This file is part of Plasma Chamber.
 //
 
-use jsonrpc_core::Error as JsonRpcError;
+//! Application error codes of Plasma JSON RPC.
+//! The codes and their `data` are listed in "JSON RPC Errors" of the README.
 
-/// invalid parameters
-pub fn invalid_params<T: std::fmt::Debug>(details: T) -> JsonRpcError {
-    JsonRpcError::invalid_params(format!("{:?}", details))
+use crate::error::{Error, ErrorKind};
+use ethereum_types::H256;
+use failure::Fail;
+use jsonrpc_core::{Error as JsonRpcError, ErrorCode};
+use log::error;
+use plasma_core::data_structure::error::{
+    Error as PlasmaCoreError, ErrorKind as PlasmaCoreErrorKind,
+};
+use serde_json::{json, Value};
+use std::fmt::Display;
+
+/// application error codes in the range reserved for server errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApplicationError {
+    DecodeFailure = -32010,
+    InvalidRange = -32011,
+    RangeConflict = -32012,
+    NoState = -32013,
+    PredicateRejected = -32014,
+    InvalidSignature = -32015,
+    UnknownBlock = -32016,
+    UnknownTransaction = -32017,
+}
+
+impl ApplicationError {
+    pub fn code(self) -> ErrorCode {
+        ErrorCode::ServerError(self as i64)
+    }
+    pub fn to_error(self, message: String, data: Value) -> JsonRpcError {
+        JsonRpcError {
+            code: self.code(),
+            message,
+            data: Some(data),
+        }
+    }
+}
+
+fn hash_to_hex(hash: &H256) -> String {
+    format!("0x{}", hex::encode(hash.as_bytes()))
+}
+
+/// failed to decode `target` from parameters
+pub fn decode_failure<T: Display>(target: &str, reason: T) -> JsonRpcError {
+    ApplicationError::DecodeFailure.to_error(
+        format!("Failed to decode {}", target),
+        json!({ "target": target, "reason": reason.to_string() }),
+    )
+}
+
+pub fn unknown_block(block_number: u64) -> JsonRpcError {
+    ApplicationError::UnknownBlock.to_error(
+        "Unknown block".to_string(),
+        json!({ "blockNumber": block_number }),
+    )
+}
+
+pub fn unknown_transaction(hash: &H256) -> JsonRpcError {
+    ApplicationError::UnknownTransaction.to_error(
+        "Unknown transaction".to_string(),
+        json!({ "transactionHash": hash_to_hex(hash) }),
+    )
+}
+
+/// map an error of the operator to an application error
+pub fn from_error(error: Error) -> JsonRpcError {
+    to_json_error(&error)
+}
+
+fn to_json_error(error: &Error) -> JsonRpcError {
+    let message = error.to_string();
+    match error.kind() {
+        ErrorKind::InvalidRange { start, end } => {
+            ApplicationError::InvalidRange.to_error(message, json!({ "start": start, "end": end }))
+        }
+        ErrorKind::RangeConflict { start, end, hash }
+        | ErrorKind::BatchConflict { start, end, hash } => ApplicationError::RangeConflict
+            .to_error(
+                message,
+                json!({ "start": start, "end": end, "transactionHash": hash_to_hex(hash) }),
+            ),
+        ErrorKind::BatchTooLarge { .. } => JsonRpcError::invalid_params(message),
+        ErrorKind::NoState { start, end } => {
+            ApplicationError::NoState.to_error(message, json!({ "start": start, "end": end }))
+        }
+        ErrorKind::PredicateRejected { start, end, reason } => ApplicationError::PredicateRejected
+            .to_error(
+                message,
+                json!({ "start": start, "end": end, "reason": reason }),
+            ),
+        ErrorKind::BatchRejected { index } => match error
+            .cause()
+            .and_then(|cause| cause.downcast_ref::<Error>())
+        {
+            Some(item_error) => with_index(to_json_error(item_error), *index),
+            None => internal_error(error),
+        },
+        ErrorKind::PlasmaCore => match error
+            .cause()
+            .and_then(|cause| cause.downcast_ref::<PlasmaCoreError>())
+        {
+            Some(cause) => match cause.kind() {
+                PlasmaCoreErrorKind::InvalidSignature => ApplicationError::InvalidSignature
+                    .to_error(message, json!({ "reason": cause.to_string() })),
+                _ => internal_error(error),
+            },
+            None => internal_error(error),
+        },
+        _ => internal_error(error),
+    }
+}
+
+/// details of internal errors are logged on the operator, and never returned to clients
+fn internal_error(error: &Error) -> JsonRpcError {
+    error!("internal error: {:?}: {}", error.kind(), error);
+    JsonRpcError::internal_error()
+}
+
+/// add the index of the item in a batch to the data
+pub fn with_index(mut error: JsonRpcError, index: usize) -> JsonRpcError {
+    let mut data = match error.data.take() {
+        Some(Value::Object(data)) => data,
+        _ => Default::default(),
+    };
+    data.insert("index".to_string(), json!(index));
+    error.data = Some(Value::Object(data));
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_error, ApplicationError};
+    use crate::error::{Error, ErrorKind};
+    use ethereum_types::H256;
+    use failure::Fail;
+    use jsonrpc_core::ErrorCode;
+    use plasma_core::data_structure::error::{
+        Error as PlasmaCoreError, ErrorKind as PlasmaCoreErrorKind,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_from_error() {
+        let error = from_error(Error::from(ErrorKind::RangeConflict {
+            start: 0,
+            end: 100,
+            hash: H256::zero(),
+        }));
+        assert_eq!(error.code, ErrorCode::ServerError(-32012));
+        assert_eq!(
+            error.data,
+            Some(json!({
+                "start": 0,
+                "end": 100,
+                "transactionHash": format!("0x{}", "00".repeat(32)),
+            }))
+        );
+        let error = from_error(Error::from(PlasmaCoreError::from(
+            PlasmaCoreErrorKind::InvalidSignature,
+        )));
+        assert_eq!(error.code, ApplicationError::InvalidSignature.code());
+        let error = from_error(Error::from(ErrorKind::Io));
+        assert_eq!(error.code, ErrorCode::InternalError);
+        assert_eq!(error.message, "Internal error");
+        assert_eq!(error.data, None);
+        let error = from_error(Error::new(
+            Error::from(ErrorKind::NoState { start: 0, end: 100 })
+                .context(ErrorKind::BatchRejected { index: 2 }),
+        ));
+        assert_eq!(error.code, ApplicationError::NoState.code());
+        assert_eq!(
+            error.data,
+            Some(json!({ "start": 0, "end": 100, "index": 2 }))
+        );
+    }
 }
@@ -10,10 +10,31 @@ extern crate plasma_core;
 extern crate rlp;
 
 use super::errors;
-use super::plasmarpc::PlasmaRpc;
-use crate::context::ChainContext;
-use jsonrpc_core::{Error as JsonRpcError, ErrorCode, Result};
+use crate::context::{ChainContext, MAX_BATCH_SIZE};
+use crate::error::{Error, ErrorKind};
+use ethereum_types::H256;
+use jsonrpc_core::Result;
 use plasma_core::data_structure::Transaction;
+use plasma_rpc::plasmarpc::{
+    InclusionProofResult, PlasmaRpc, StateUpdateJson, TransactionResult, TransactionStatus,
+};
+
+/// parse a hex string of 32 bytes hash with or without 0x prefix
+fn parse_hash(hash: &str) -> Result<H256> {
+    let bytes = hex::decode(hash.trim_start_matches("0x"))
+        .map_err(|err| errors::decode_failure("hash", err))?;
+    if bytes.len() == 32 {
+        Ok(H256::from_slice(&bytes))
+    } else {
+        Err(errors::decode_failure("hash", "hash should be 32 bytes"))
+    }
+}
+
+fn decode_transaction(message: &str) -> Result<Transaction> {
+    let abi_bytes =
+        hex::decode(message).map_err(|err| errors::decode_failure("transaction", err))?;
+    Transaction::from_abi(&abi_bytes).map_err(|err| errors::decode_failure("transaction", err))
+}
 
 /// Plasma JSON RPC implementation.
 #[derive(Default)]
@@ -27,35 +48,156 @@ impl PlasmaRpcImpl {
             chain_context: Default::default(),
         }
     }
+    pub fn from_chain_context(chain_context: ChainContext) -> PlasmaRpcImpl {
+        PlasmaRpcImpl { chain_context }
+    }
 }
 
 impl PlasmaRpc for PlasmaRpcImpl {
     fn protocol_version(&self) -> Result<String> {
         Ok("0.1.0".into())
     }
-    fn send_transaction(&self, message: String) -> Result<bool> {
-        let abi_bytes = hex::decode(message).map_err(errors::invalid_params)?;
-        let transaction: Transaction =
-            Transaction::from_abi(&abi_bytes).map_err(errors::invalid_params)?;
-        self.chain_context.append(&transaction);
-        Ok(true)
+    fn send_transaction(&self, message: String) -> Result<String> {
+        let transaction = decode_transaction(&message)?;
+        self.chain_context
+            .append(&transaction)
+            .map(|receipt| hex::encode(receipt.to_abi()))
+            .map_err(errors::from_error)
+    }
+    fn send_transactions(
+        &self,
+        messages: Vec<String>,
+        atomic: Option<bool>,
+    ) -> Result<Vec<TransactionResult>> {
+        let atomic = atomic.unwrap_or(true);
+        if messages.len() > MAX_BATCH_SIZE {
+            return Err(errors::from_error(Error::from(ErrorKind::BatchTooLarge {
+                size: messages.len(),
+                max: MAX_BATCH_SIZE,
+            })));
+        }
+        let decoded: Vec<Result<Transaction>> = messages
+            .iter()
+            .map(|message| decode_transaction(message))
+            .collect();
+        if atomic {
+            if let Some((index, Err(err))) = decoded
+                .iter()
+                .enumerate()
+                .find(|(_, result)| result.is_err())
+            {
+                return Err(errors::with_index(err.clone(), index));
+            }
+        }
+        let transactions: Vec<Transaction> = decoded
+            .iter()
+            .filter_map(|result| result.as_ref().ok().cloned())
+            .collect();
+        let mut appended = self
+            .chain_context
+            .append_batch(&transactions, atomic)
+            .map_err(errors::from_error)?
+            .into_iter();
+        Ok(decoded
+            .into_iter()
+            .map(|result| {
+                match result.and_then(|_| {
+                    appended
+                        .next()
+                        .expect("a result for each decoded transaction")
+                        .map_err(errors::from_error)
+                }) {
+                    Ok(receipt) => TransactionResult::Receipt(hex::encode(receipt.to_abi())),
+                    Err(err) => TransactionResult::Error(err),
+                }
+            })
+            .collect())
+    }
+    fn simulate_transaction(&self, message: String) -> Result<Vec<StateUpdateJson>> {
+        let transaction = decode_transaction(&message)?;
+        self.chain_context
+            .simulate(&transaction)
+            .map(|state_updates| state_updates.iter().map(StateUpdateJson::new).collect())
+            .map_err(errors::from_error)
+    }
+    fn get_block_number(&self) -> Result<u64> {
+        self.chain_context
+            .get_block_number()
+            .map_err(errors::from_error)
+    }
+    fn get_block(&self, number: u64) -> Result<String> {
+        self.chain_context
+            .get_block(number)
+            .map_err(errors::from_error)?
+            .map(|block| hex::encode(rlp::encode(&block)))
+            .ok_or_else(|| errors::unknown_block(number))
     }
-    fn generate_block(&self) -> Result<String> {
+    fn get_transaction(&self, hash: String) -> Result<String> {
+        let hash = parse_hash(&hash)?;
         self.chain_context
-            .generate()
-            .map(|block| rlp::encode(&block))
-            .map(hex::encode)
-            .map_err(|_err| JsonRpcError::new(ErrorCode::InternalError))
+            .get_transaction(&hash)
+            .map_err(errors::from_error)?
+            .map(|transaction| hex::encode(transaction.to_abi()))
+            .ok_or_else(|| errors::unknown_transaction(&hash))
+    }
+    fn get_state_updates(&self, start: u64, end: u64) -> Result<Vec<String>> {
+        self.chain_context
+            .get_state_updates(start, end)
+            .map(|state_updates| {
+                state_updates
+                    .iter()
+                    .map(|state_update| hex::encode(state_update.to_abi()))
+                    .collect()
+            })
+            .map_err(errors::from_error)
+    }
+    fn get_transaction_status(&self, hash: String) -> Result<TransactionStatus> {
+        self.chain_context
+            .get_transaction_status(&parse_hash(&hash)?)
+            .map_err(errors::from_error)
+    }
+    fn get_inclusion_proof(&self, hash: String) -> Result<InclusionProofResult> {
+        let hash = parse_hash(&hash)?;
+        self.chain_context
+            .get_inclusion_proof(&hash)
+            .map_err(errors::from_error)?
+            .map(|(block_number, inclusion_proof)| {
+                InclusionProofResult::new(block_number, &inclusion_proof)
+            })
+            .ok_or_else(|| errors::unknown_transaction(&hash))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::PlasmaRpc;
     use super::PlasmaRpcImpl;
-    use ethereum_types::{Address, H256};
+    use crate::context::ChainContext;
+    use crate::test_utils::{create_deposit, create_state_update, create_transaction};
+    use ethereum_types::H256;
+    use jsonrpc_core::futures::Future;
+    use jsonrpc_core_client::RpcError;
     use jsonrpc_http_server::jsonrpc_core::IoHandler;
-    use plasma_core::data_structure::{Transaction, Witness};
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::data_structure::{InclusionReceipt, Transaction};
+    use plasma_rpc::client::OperatorClient;
+    use plasma_rpc::plasmarpc::{InclusionProofResult, PlasmaRpc, TransactionStatus};
+
+    /// calls the operator in process with the typed client, and waits for the result
+    fn call<T, F>(
+        chain_context: &ChainContext,
+        request: impl FnOnce(&OperatorClient) -> F,
+    ) -> Result<T, RpcError>
+    where
+        F: Future<Item = T, Error = RpcError>,
+    {
+        let mut io = IoHandler::new();
+        io.extend_with(PlasmaRpcImpl::from_chain_context(chain_context.clone()).to_delegate());
+        let (client, rpc_client) = OperatorClient::connect_local(io);
+        let future = request(&client);
+        // the transport finishes once every client is dropped
+        drop(client);
+        future.join(rpc_client).wait().map(|(result, ())| result)
+    }
 
     #[test]
     fn test_protocol_version() {
@@ -74,18 +216,13 @@ mod tests {
     fn test_send_transaction() {
         let mut io = IoHandler::new();
 
-        let rpc = PlasmaRpcImpl::new();
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let chain_context = ChainContext::new(&operator_key);
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let rpc = PlasmaRpcImpl::from_chain_context(chain_context);
         io.extend_with(rpc.to_delegate());
 
-        let parameters_bytes = Vec::from(&b"parameters"[..]);
-        let transaction = Transaction::new(
-            Address::zero(),
-            0,
-            100,
-            Transaction::create_method_id(&b"send(address)"[..]),
-            &parameters_bytes,
-            &Witness::new(H256::zero(), H256::zero(), 0),
-        );
+        let transaction = create_transaction(0, 100);
         let encoded = transaction.to_abi();
 
         let request = format!(
@@ -97,9 +234,13 @@ mod tests {
             }}"#,
             hex::encode(encoded),
         );
-        let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+        let receipt = InclusionReceipt::sign(&transaction.hash(), 1, &operator_key).unwrap();
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#,
+            hex::encode(receipt.to_abi())
+        );
 
-        assert_eq!(io.handle_request_sync(&request), Some(response.to_string()));
+        assert_eq!(io.handle_request_sync(&request), Some(response));
     }
 
     /*
@@ -122,4 +263,268 @@ mod tests {
     }
     */
 
+    #[test]
+    fn test_get_block() {
+        let mut io = IoHandler::new();
+
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let transaction = create_transaction(0, 100);
+        assert!(chain_context.append(&transaction).is_ok());
+        let block = chain_context.generate().unwrap();
+        let rpc = PlasmaRpcImpl::from_chain_context(chain_context);
+        io.extend_with(rpc.to_delegate());
+
+        let request = r#"{"jsonrpc": "2.0", "method": "getBlockNumber", "params": [], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","result":1,"id":1}"#;
+        assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+
+        let request = r#"{"jsonrpc": "2.0", "method": "getBlock", "params": [1], "id": 1}"#;
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#,
+            hex::encode(rlp::encode(&block))
+        );
+        assert_eq!(io.handle_request_sync(request), Some(response));
+
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "getTransaction", "params": ["0x{}"], "id": 1}}"#,
+            hex::encode(transaction.hash().as_bytes())
+        );
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#,
+            hex::encode(transaction.to_abi())
+        );
+        assert_eq!(io.handle_request_sync(&request), Some(response));
+
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "getTransactionStatus", "params": ["{}"], "id": 1}}"#,
+            hex::encode(transaction.hash().as_bytes())
+        );
+        let response = r#"{"jsonrpc":"2.0","result":{"status":"included","blockNumber":1},"id":1}"#;
+        assert_eq!(io.handle_request_sync(&request), Some(response.to_string()));
+
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "getInclusionProof", "params": ["{}"], "id": 1}}"#,
+            hex::encode(transaction.hash().as_bytes())
+        );
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#,
+            serde_json::to_string(&InclusionProofResult::new(
+                1,
+                &block.get_inclusion_proof(0).unwrap()
+            ))
+            .unwrap()
+        );
+        assert_eq!(io.handle_request_sync(&request), Some(response));
+    }
+
+    #[test]
+    fn test_get_state_updates() {
+        let mut io = IoHandler::new();
+
+        let chain_context: ChainContext = Default::default();
+        let deposit = create_deposit(0, 100);
+        assert!(chain_context.deposit(&deposit).is_ok());
+        let rpc = PlasmaRpcImpl::from_chain_context(chain_context);
+        io.extend_with(rpc.to_delegate());
+
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "getStateUpdates", "params": [0, 100], "id": 1}"#;
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","result":["{}"],"id":1}}"#,
+            hex::encode(deposit.to_abi())
+        );
+        assert_eq!(io.handle_request_sync(request), Some(response));
+    }
+
+    #[test]
+    fn test_get_unknown_block() {
+        let mut io = IoHandler::new();
+
+        let rpc = PlasmaRpcImpl::new();
+        io.extend_with(rpc.to_delegate());
+
+        let request = r#"{"jsonrpc": "2.0", "method": "getBlock", "params": [1], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","error":{"code":-32016,"message":"Unknown block","data":{"blockNumber":1}},"id":1}"#;
+        assert_eq!(io.handle_request_sync(request), Some(response.to_string()));
+    }
+
+    #[test]
+    fn test_reject_transaction() {
+        let mut io = IoHandler::new();
+
+        let rpc = PlasmaRpcImpl::new();
+        io.extend_with(rpc.to_delegate());
+
+        let transaction = create_transaction(0, 100);
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "sendTransaction", "params": ["{}"], "id": 1}}"#,
+            hex::encode(transaction.to_abi())
+        );
+        let response = r#"{"jsonrpc":"2.0","error":{"code":-32013,"message":"Invalid transaction: range [0, 100) has no state","data":{"end":100,"start":0}},"id":1}"#;
+        assert_eq!(io.handle_request_sync(&request), Some(response.to_string()));
+    }
+
+    #[test]
+    fn test_send_transactions() {
+        let mut io = IoHandler::new();
+
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let chain_context = ChainContext::new(&operator_key);
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let rpc = PlasmaRpcImpl::from_chain_context(chain_context.clone());
+        io.extend_with(rpc.to_delegate());
+
+        let transactions: Vec<Transaction> = [(0, 100), (50, 150), (100, 200)]
+            .iter()
+            .map(|(start, end)| create_transaction(*start, *end))
+            .collect();
+        let messages: Vec<String> = transactions
+            .iter()
+            .map(|transaction| format!(r#""{}""#, hex::encode(transaction.to_abi())))
+            .collect();
+
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "sendTransactions", "params": [[{}]], "id": 1}}"#,
+            messages.join(",")
+        );
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","error":{{"code":-32012,"message":"Invalid transaction: range [50, 150) conflicts with transaction {:?} of the same batch","data":{{"end":150,"index":1,"start":50,"transactionHash":"0x{}"}}}},"id":1}}"#,
+            transactions[0].hash(),
+            hex::encode(transactions[0].hash().as_bytes())
+        );
+        assert_eq!(io.handle_request_sync(&request), Some(response));
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "sendTransactions", "params": [[{}, "zz"], false], "id": 1}}"#,
+            messages.join(",")
+        );
+        let receipt0 = InclusionReceipt::sign(&transactions[0].hash(), 1, &operator_key).unwrap();
+        let receipt2 = InclusionReceipt::sign(&transactions[2].hash(), 1, &operator_key).unwrap();
+        let response = io.handle_request_sync(&request).unwrap();
+        assert!(response.starts_with(&format!(
+            r#"{{"jsonrpc":"2.0","result":[{{"receipt":"{}"}},{{"error":{{"code":-32012"#,
+            hex::encode(receipt0.to_abi())
+        )));
+        assert!(response.contains(&format!(
+            r#"{{"receipt":"{}"}},{{"error":{{"code":-32010"#,
+            hex::encode(receipt2.to_abi())
+        )));
+        assert_eq!(chain_context.get_pending_transaction_count(), 2);
+    }
+
+    #[test]
+    fn test_simulate_transaction() {
+        let mut io = IoHandler::new();
+
+        let chain_context: ChainContext = Default::default();
+        let deposit = create_deposit(0, 100);
+        assert!(chain_context.deposit(&deposit).is_ok());
+        let rpc = PlasmaRpcImpl::from_chain_context(chain_context.clone());
+        io.extend_with(rpc.to_delegate());
+
+        let transaction = create_transaction(0, 50);
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "simulateTransaction", "params": ["{}"], "id": 1}}"#,
+            hex::encode(transaction.to_abi())
+        );
+        let expected = create_state_update(0, 50, 1);
+        let zero = format!("0x{}", "00".repeat(20));
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","result":[{{"abi":"{}","start":0,"end":50,"blockNumber":1,"plasmaContract":"{}","predicate":"{}","data":"0x{}"}}],"id":1}}"#,
+            hex::encode(expected.to_abi()),
+            zero,
+            zero,
+            hex::encode(expected.get_state_object().get_data())
+        );
+        assert_eq!(io.handle_request_sync(&request), Some(response));
+        assert_eq!(chain_context.get_pending_transaction_count(), 0);
+        assert_eq!(
+            chain_context.get_state_updates(0, 100).unwrap(),
+            vec![deposit]
+        );
+    }
+
+    #[test]
+    fn test_send_transaction_with_client() {
+        let operator_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let chain_context = ChainContext::new(&operator_key);
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let transactions = vec![create_transaction(0, 100), create_transaction(50, 150)];
+
+        let receipt = call(&chain_context, |client| {
+            client.send_transaction(&transactions[0])
+        })
+        .unwrap();
+        assert_eq!(
+            receipt,
+            InclusionReceipt::sign(&transactions[0].hash(), 1, &operator_key).unwrap()
+        );
+
+        let results = call(&chain_context, |client| {
+            client.send_transactions(&transactions[1..], false)
+        })
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].as_ref().unwrap_err().code.code(), -32012);
+    }
+
+    #[test]
+    fn test_get_block_with_client() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let transaction = create_transaction(0, 100);
+        assert!(chain_context.append(&transaction).is_ok());
+        let block = chain_context.generate().unwrap();
+
+        let hash = transaction.hash();
+        let (number, fetched_block, fetched_transaction, status) = call(&chain_context, |client| {
+            client.get_block_number().join4(
+                client.get_block(1),
+                client.get_transaction(&hash),
+                client.get_transaction_status(&hash),
+            )
+        })
+        .unwrap();
+        assert_eq!(number, 1);
+        assert_eq!(fetched_block, block);
+        assert_eq!(fetched_transaction, transaction);
+        assert_eq!(status, TransactionStatus::Included { block_number: 1 });
+
+        let (block_number, inclusion_proof) =
+            call(&chain_context, |client| client.get_inclusion_proof(&hash)).unwrap();
+        assert_eq!(block_number, 1);
+        assert_eq!(Some(inclusion_proof), block.get_inclusion_proof(0));
+        match call(&chain_context, |client| {
+            client.get_inclusion_proof(&H256::zero())
+        }) {
+            Err(RpcError::JsonRpcError(err)) => assert_eq!(err.code.code(), -32017),
+            _ => panic!("unknown transaction should be an error"),
+        }
+    }
+
+    #[test]
+    fn test_get_state_updates_with_client() {
+        let chain_context: ChainContext = Default::default();
+        let deposit = create_deposit(0, 100);
+        assert!(chain_context.deposit(&deposit).is_ok());
+
+        let (state_updates, simulated) = call(&chain_context, |client| {
+            client
+                .get_state_updates(0, 100)
+                .join(client.simulate_transaction(&create_transaction(0, 50)))
+        })
+        .unwrap();
+        assert_eq!(state_updates, vec![deposit]);
+        assert_eq!(simulated, vec![create_state_update(0, 50, 1)]);
+    }
+
+    #[test]
+    fn test_get_unknown_block_with_client() {
+        match call(&Default::default(), |client| client.get_block(1)) {
+            Err(RpcError::JsonRpcError(err)) => assert_eq!(err.code.code(), -32016),
+            _ => panic!("unknown block should be an error"),
+        }
+    }
 }
@@ -0,0 +1,38 @@
+//
+// Created on Mon Jun 10 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+extern crate jsonrpc_core;
+extern crate jsonrpc_pubsub;
+
+use jsonrpc_core::{Result, Value};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::typed::Subscriber;
+use jsonrpc_pubsub::SubscriptionId;
+
+/// Plasma JSON RPC subscriptions over WebSocket.
+/// `subscribe("newBlocks")` notifies blocks encoded by RLP,
+/// `subscribe("rangeUpdates", start, end)` and `subscribe("address", owner)`
+/// notify state updates encoded by ABI.
+#[rpc]
+pub trait PlasmaPubSub {
+    type Metadata;
+
+    /// subscribe to notifications of the chain
+    #[pubsub(subscription = "subscription", subscribe, name = "subscribe")]
+    fn subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<String>,
+        kind: String,
+        first: Option<Value>,
+        second: Option<Value>,
+    );
+
+    /// cancel a subscription
+    #[pubsub(subscription = "subscription", unsubscribe, name = "unsubscribe")]
+    fn unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+}
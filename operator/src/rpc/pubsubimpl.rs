@@ -0,0 +1,437 @@
+//
+// Created on Mon Jun 10 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+extern crate ethabi;
+extern crate jsonrpc_core;
+extern crate jsonrpc_pubsub;
+
+use super::pubsub::PlasmaPubSub;
+use crate::context::{ChainContext, ChainListener};
+use ethabi::Token;
+use ethereum_types::Address;
+use jsonrpc_core::futures::Future;
+use jsonrpc_core::{Error as JsonRpcError, Result, Value};
+use jsonrpc_pubsub::typed::{Sink, Subscriber};
+use jsonrpc_pubsub::{Session, SubscriptionId};
+use log::warn;
+use parking_lot::RwLock;
+use plasma_core::data_structure::{Block, StateUpdate};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+/// what a subscriber is notified of
+#[derive(Clone, Debug, PartialEq)]
+pub enum Topic {
+    NewBlocks,
+    RangeUpdates { start: u64, end: u64 },
+    Address(Address),
+}
+
+impl Topic {
+    pub fn parse(kind: &str, first: Option<Value>, second: Option<Value>) -> Result<Topic> {
+        match kind {
+            "newBlocks" => Ok(Topic::NewBlocks),
+            "rangeUpdates" => {
+                let start = first.as_ref().and_then(Value::as_u64);
+                let end = second.as_ref().and_then(Value::as_u64);
+                match (start, end) {
+                    (Some(start), Some(end)) if start < end => {
+                        Ok(Topic::RangeUpdates { start, end })
+                    }
+                    _ => Err(JsonRpcError::invalid_params(
+                        "rangeUpdates requires start and end",
+                    )),
+                }
+            }
+            "address" => first
+                .as_ref()
+                .and_then(Value::as_str)
+                .and_then(|owner| hex::decode(owner.trim_start_matches("0x")).ok())
+                .filter(|bytes| bytes.len() == 20)
+                .map(|bytes| Topic::Address(Address::from_slice(&bytes)))
+                .ok_or_else(|| JsonRpcError::invalid_params("address requires an owner address")),
+            _ => Err(JsonRpcError::invalid_params(format!(
+                "Unknown subscription {}",
+                kind
+            ))),
+        }
+    }
+    /// state updates of ownership predicate hold an owner address as its data
+    fn matches(&self, state_update: &StateUpdate) -> bool {
+        match self {
+            Topic::NewBlocks => false,
+            Topic::RangeUpdates { start, end } => {
+                state_update.get_start() < *end && state_update.get_end() > *start
+            }
+            Topic::Address(owner) => {
+                state_update.get_state_object().get_data()
+                    == &ethabi::encode(&[Token::Address(*owner)])[..]
+            }
+        }
+    }
+}
+
+/// the number of notifications queued for a subscriber at most
+pub const QUEUE_SIZE: usize = 64;
+
+/// identifies the session of a subscription while the session is alive
+fn session_id(session: &Session) -> usize {
+    session as *const Session as usize
+}
+
+/// a subscription and the queue of its notifications
+struct Subscription {
+    topic: Topic,
+    session: usize,
+    queue: mpsc::SyncSender<String>,
+}
+
+impl Subscription {
+    /// notifications are sent to the sink from a thread of the subscription in order
+    fn start(topic: Topic, session: usize, sink: Sink<String>) -> Self {
+        let (queue, receiver) = mpsc::sync_channel::<String>(QUEUE_SIZE);
+        thread::spawn(move || {
+            for message in receiver.iter() {
+                // fails if the session is closed, and its subscriptions are removed when it is dropped
+                if sink.notify(Ok(message)).wait().is_err() {
+                    break;
+                }
+            }
+        });
+        Subscription {
+            topic,
+            session,
+            queue,
+        }
+    }
+}
+
+/// active subscriptions notified by ChainContext.
+/// Notifications are queued for each subscriber without blocking,
+/// so that a slow subscriber never blocks the chain or other subscribers.
+/// A subscriber whose queue is full is dropped.
+#[derive(Default)]
+pub struct Subscriptions {
+    sinks: RwLock<HashMap<SubscriptionId, Subscription>>,
+}
+
+impl Subscriptions {
+    fn notify<F>(&self, filter: F, message: &str)
+    where
+        F: Fn(&Topic) -> bool,
+    {
+        let dropped: Vec<SubscriptionId> = self
+            .sinks
+            .read()
+            .iter()
+            .filter(|(_, subscription)| filter(&subscription.topic))
+            .filter(|(_, subscription)| subscription.queue.try_send(message.to_string()).is_err())
+            .map(|(id, _)| id.clone())
+            .collect();
+        if !dropped.is_empty() {
+            let mut sinks = self.sinks.write();
+            for id in dropped.iter() {
+                warn!(
+                    "dropped subscription {:?} which doesn't keep up with notifications",
+                    id
+                );
+                sinks.remove(id);
+            }
+        }
+    }
+}
+
+impl ChainListener for Subscriptions {
+    fn on_new_block(&self, block: &Block) {
+        self.notify(
+            |topic| topic == &Topic::NewBlocks,
+            &hex::encode(rlp::encode(block)),
+        );
+    }
+    fn on_state_update(&self, state_update: &StateUpdate) {
+        self.notify(
+            |topic| topic.matches(state_update),
+            &hex::encode(state_update.to_abi()),
+        );
+    }
+}
+
+/// Plasma JSON RPC subscriptions implementation.
+pub struct PlasmaPubSubImpl {
+    next_id: AtomicUsize,
+    subscriptions: Arc<Subscriptions>,
+}
+
+impl PlasmaPubSubImpl {
+    pub fn new(chain_context: &ChainContext) -> PlasmaPubSubImpl {
+        let subscriptions = Arc::new(Subscriptions::default());
+        chain_context.add_listener(subscriptions.clone());
+        PlasmaPubSubImpl {
+            next_id: AtomicUsize::new(0),
+            subscriptions,
+        }
+    }
+}
+
+impl PlasmaPubSub for PlasmaPubSubImpl {
+    type Metadata = Arc<Session>;
+
+    fn subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<String>,
+        kind: String,
+        first: Option<Value>,
+        second: Option<Value>,
+    ) {
+        match Topic::parse(&kind, first, second) {
+            Ok(topic) => {
+                let id = SubscriptionId::Number(self.next_id.fetch_add(1, Ordering::SeqCst) as u64);
+                if let Ok(sink) = subscriber.assign_id(id.clone()) {
+                    self.subscriptions.sinks.write().insert(
+                        id.clone(),
+                        Subscription::start(topic, session_id(&meta), sink),
+                    );
+                    let subscriptions = self.subscriptions.clone();
+                    meta.on_drop(move || {
+                        subscriptions.sinks.write().remove(&id);
+                    });
+                }
+            }
+            Err(err) => {
+                let _ = subscriber.reject(err);
+            }
+        }
+    }
+    /// a subscription is cancelled only by the session which made it
+    fn unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+        let session = match meta {
+            Some(meta) => session_id(&meta),
+            None => return Ok(false),
+        };
+        let mut sinks = self.subscriptions.sinks.write();
+        if sinks
+            .get(&id)
+            .map_or(false, |subscription| subscription.session == session)
+        {
+            sinks.remove(&id);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlasmaPubSub;
+    use super::{PlasmaPubSubImpl, Subscriptions, Topic, QUEUE_SIZE};
+    use crate::context::ChainContext;
+    use crate::test_utils::{create_deposit, create_transaction};
+    use ethabi::Token;
+    use ethereum_types::Address;
+    use jsonrpc_core::futures::sync::mpsc;
+    use jsonrpc_core::futures::{Future, Stream};
+    use jsonrpc_core::Value;
+    use jsonrpc_pubsub::{PubSubHandler, Session};
+    use plasma_core::data_structure::{StateObject, StateUpdate};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn create_handler(chain_context: &ChainContext) -> PubSubHandler<Arc<Session>> {
+        create_handler_with_subscriptions(chain_context).0
+    }
+
+    fn create_handler_with_subscriptions(
+        chain_context: &ChainContext,
+    ) -> (PubSubHandler<Arc<Session>>, Arc<Subscriptions>) {
+        let mut io = PubSubHandler::default();
+        let pubsub = PlasmaPubSubImpl::new(chain_context);
+        let subscriptions = pubsub.subscriptions.clone();
+        io.extend_with(pubsub.to_delegate());
+        (io, subscriptions)
+    }
+
+    #[test]
+    fn test_parse_topic() {
+        assert_eq!(
+            Topic::parse("rangeUpdates", Some(Value::from(0)), Some(Value::from(100))).unwrap(),
+            Topic::RangeUpdates { start: 0, end: 100 }
+        );
+        assert!(Topic::parse("rangeUpdates", Some(Value::from(0)), None).is_err());
+        assert_eq!(
+            Topic::parse(
+                "address",
+                Some(Value::from("0x0000000000000000000000000000000000000001")),
+                None
+            )
+            .unwrap(),
+            Topic::Address(Address::from_low_u64_be(1))
+        );
+        assert!(Topic::parse("unknown", None, None).is_err());
+    }
+
+    #[test]
+    fn test_subscribe_new_blocks() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let io = create_handler(&chain_context);
+        let (sender, receiver) = mpsc::channel(8);
+        let session = Arc::new(Session::new(sender));
+
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "subscribe", "params": ["newBlocks"], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","result":0,"id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(request, session.clone()),
+            Some(response.to_string())
+        );
+
+        let transaction = create_transaction(0, 100);
+        assert!(chain_context.append(&transaction).is_ok());
+        let block = chain_context.generate().unwrap();
+        let notification = format!(
+            r#"{{"jsonrpc":"2.0","method":"subscription","params":{{"result":"{}","subscription":0}}}}"#,
+            hex::encode(rlp::encode(&block))
+        );
+        let (received, _) = receiver.into_future().wait().ok().unwrap();
+        assert_eq!(received, Some(notification));
+
+        let request = r#"{"jsonrpc": "2.0", "method": "unsubscribe", "params": [0], "id": 1}"#;
+        let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(request, session),
+            Some(response.to_string())
+        );
+    }
+
+    #[test]
+    fn test_subscribe_address() {
+        let chain_context: ChainContext = Default::default();
+        let io = create_handler(&chain_context);
+        let (sender, receiver) = mpsc::channel(8);
+        let session = Arc::new(Session::new(sender));
+
+        let owner = Address::from_low_u64_be(1);
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "subscribe", "params": ["address", "0x{}"], "id": 1}}"#,
+            hex::encode(owner.as_bytes())
+        );
+        let response = r#"{"jsonrpc":"2.0","result":0,"id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(&request, session.clone()),
+            Some(response.to_string())
+        );
+
+        let others = create_deposit(0, 100);
+        let owned = StateUpdate::new(
+            &StateObject::new(Address::zero(), &ethabi::encode(&[Token::Address(owner)])),
+            100,
+            200,
+            0,
+            Address::zero(),
+        );
+        assert!(chain_context.deposit(&others).is_ok());
+        assert!(chain_context.deposit(&owned).is_ok());
+        let notification = format!(
+            r#"{{"jsonrpc":"2.0","method":"subscription","params":{{"result":"{}","subscription":0}}}}"#,
+            hex::encode(owned.to_abi())
+        );
+        let (received, _) = receiver.into_future().wait().ok().unwrap();
+        assert_eq!(received, Some(notification));
+    }
+
+    #[test]
+    fn test_slow_subscriber_does_not_block_chain() {
+        let chain_context: ChainContext = Default::default();
+        let io = create_handler(&chain_context);
+        // the client never reads notifications
+        let (sender, _receiver) = mpsc::channel(0);
+        let session = Arc::new(Session::new(sender));
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "subscribe", "params": ["newBlocks"], "id": 1}"#;
+        assert!(io.handle_request_sync(request, session.clone()).is_some());
+        for number in 1..=10 {
+            assert_eq!(chain_context.generate().unwrap().get_number(), number);
+        }
+    }
+
+    #[test]
+    fn test_slow_subscriber_does_not_delay_others() {
+        let chain_context: ChainContext = Default::default();
+        let (io, subscriptions) = create_handler_with_subscriptions(&chain_context);
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "subscribe", "params": ["newBlocks"], "id": 1}"#;
+        // the first client never reads notifications
+        let (slow_sender, _slow_receiver) = mpsc::channel(0);
+        let slow_session = Arc::new(Session::new(slow_sender));
+        assert!(io
+            .handle_request_sync(request, slow_session.clone())
+            .is_some());
+        let (sender, receiver) = mpsc::channel(8);
+        let session = Arc::new(Session::new(sender));
+        assert!(io.handle_request_sync(request, session.clone()).is_some());
+
+        let count = QUEUE_SIZE * 2;
+        let (received_sender, received) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            for notification in receiver.wait() {
+                let _ = received_sender.send(notification);
+            }
+        });
+        for _ in 0..count {
+            chain_context.generate().unwrap();
+            assert!(received.recv_timeout(Duration::from_secs(5)).is_ok());
+        }
+        // the slow subscriber is dropped once its queue is full
+        assert_eq!(subscriptions.sinks.read().len(), 1);
+    }
+
+    #[test]
+    fn test_unsubscribe_only_own_subscriptions() {
+        let chain_context: ChainContext = Default::default();
+        let (io, subscriptions) = create_handler_with_subscriptions(&chain_context);
+        let (sender, _receiver) = mpsc::channel(8);
+        let session = Arc::new(Session::new(sender));
+        let (other_sender, _other_receiver) = mpsc::channel(8);
+        let other_session = Arc::new(Session::new(other_sender));
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "subscribe", "params": ["newBlocks"], "id": 1}"#;
+        assert!(io.handle_request_sync(request, session.clone()).is_some());
+
+        let request = r#"{"jsonrpc": "2.0", "method": "unsubscribe", "params": [0], "id": 1}"#;
+        let rejected = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(request, other_session),
+            Some(rejected.to_string())
+        );
+        assert_eq!(subscriptions.sinks.read().len(), 1);
+        let accepted = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+        assert_eq!(
+            io.handle_request_sync(request, session),
+            Some(accepted.to_string())
+        );
+        assert!(subscriptions.sinks.read().is_empty());
+    }
+
+    #[test]
+    fn test_remove_subscriptions_of_dropped_session() {
+        let chain_context: ChainContext = Default::default();
+        let (io, subscriptions) = create_handler_with_subscriptions(&chain_context);
+        let (sender, _receiver) = mpsc::channel(8);
+        let session = Arc::new(Session::new(sender));
+        let request =
+            r#"{"jsonrpc": "2.0", "method": "subscribe", "params": ["newBlocks"], "id": 1}"#;
+        assert!(io.handle_request_sync(request, session.clone()).is_some());
+        assert_eq!(subscriptions.sinks.read().len(), 1);
+        drop(session);
+        assert!(subscriptions.sinks.read().is_empty());
+    }
+}
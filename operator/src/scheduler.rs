@@ -0,0 +1,239 @@
+//
+// Created on Wed Jun 12 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+/// block production scheduler.
+use super::context::{ChainContext, ChainListener};
+use log::{error, info};
+use parking_lot::Mutex;
+use plasma_core::data_structure::Transaction;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Options for block production scheduler.
+/// A block is generated when any of the limits is reached.
+pub struct SchedulerOption {
+    interval: Duration,
+    max_transactions: usize,
+    max_bytes: usize,
+}
+
+impl Default for SchedulerOption {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            max_transactions: 1000,
+            max_bytes: 1024 * 1024,
+        }
+    }
+}
+
+impl SchedulerOption {
+    pub fn new(interval: Duration, max_transactions: usize, max_bytes: usize) -> Self {
+        Self {
+            interval,
+            max_transactions,
+            max_bytes,
+        }
+    }
+}
+
+enum Message {
+    /// generate a block now
+    Trigger,
+    /// check the size of pending transactions
+    Check,
+    Shutdown,
+}
+
+/// notifies the scheduler of new pending transactions
+struct PoolListener {
+    sender: Mutex<Sender<Message>>,
+}
+
+impl ChainListener for PoolListener {
+    fn on_new_transaction(&self, _transaction: &Transaction) {
+        // the scheduler may have been shut down
+        let _ = self.sender.lock().send(Message::Check);
+    }
+}
+
+/// Scheduler generates blocks on its own thread until it's shut down or dropped.
+pub struct Scheduler {
+    sender: Sender<Message>,
+    handle: Option<JoinHandle<()>>,
+    chain_context: ChainContext,
+    listener: Arc<dyn ChainListener + Send + Sync>,
+}
+
+impl Scheduler {
+    pub fn start(options: SchedulerOption, chain_context: &ChainContext) -> Scheduler {
+        let (sender, receiver) = channel();
+        let listener: Arc<dyn ChainListener + Send + Sync> = Arc::new(PoolListener {
+            sender: Mutex::new(sender.clone()),
+        });
+        chain_context.add_listener(listener.clone());
+        let context = chain_context.clone();
+        let handle = spawn(move || run(&options, &context, &receiver));
+        Scheduler {
+            sender,
+            handle: Some(handle),
+            chain_context: chain_context.clone(),
+            listener,
+        }
+    }
+    /// generate a block regardless of the limits
+    pub fn trigger(&self) {
+        let _ = self.sender.send(Message::Trigger);
+    }
+    /// stop the scheduler and wait for the block being generated
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+    fn stop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.chain_context.remove_listener(&self.listener);
+            let _ = self.sender.send(Message::Shutdown);
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn is_pool_full(options: &SchedulerOption, chain_context: &ChainContext) -> bool {
+    chain_context.get_pending_transaction_count() >= options.max_transactions
+        || chain_context.get_pending_transaction_bytes() >= options.max_bytes
+}
+
+fn generate(chain_context: &ChainContext) {
+    match chain_context.generate() {
+        Ok(block) => info!(
+            "generated block {} with {} transactions",
+            block.get_number(),
+            block.get_transactions().len()
+        ),
+        Err(err) => error!("failed to generate block: {}", err),
+    }
+}
+
+fn run(options: &SchedulerOption, chain_context: &ChainContext, receiver: &Receiver<Message>) {
+    let mut deadline = Instant::now() + options.interval;
+    loop {
+        let now = Instant::now();
+        let timeout = if deadline > now {
+            deadline - now
+        } else {
+            Duration::from_secs(0)
+        };
+        let should_generate = match receiver.recv_timeout(timeout) {
+            Ok(Message::Trigger) => true,
+            Ok(Message::Check) => is_pool_full(options, chain_context),
+            // empty blocks aren't generated on interval
+            Err(RecvTimeoutError::Timeout) => {
+                deadline = Instant::now() + options.interval;
+                chain_context.get_pending_transaction_count() > 0
+            }
+            Ok(Message::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if should_generate {
+            generate(chain_context);
+            deadline = Instant::now() + options.interval;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scheduler, SchedulerOption};
+    use crate::context::ChainContext;
+    use crate::test_utils::{create_deposit, create_transaction};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    fn wait_for_block(chain_context: &ChainContext, block_number: u64) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if chain_context.get_block_number().unwrap() >= block_number {
+                return true;
+            }
+            sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn test_interval() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let scheduler = Scheduler::start(
+            SchedulerOption::new(Duration::from_millis(50), 1000, 1024 * 1024),
+            &chain_context,
+        );
+        sleep(Duration::from_millis(120));
+        assert_eq!(chain_context.get_block_number().unwrap(), 0);
+        assert!(chain_context.append(&create_transaction(0, 100)).is_ok());
+        assert!(wait_for_block(&chain_context, 1));
+        scheduler.shutdown();
+    }
+
+    #[test]
+    fn test_pool_size() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let scheduler = Scheduler::start(
+            SchedulerOption::new(Duration::from_secs(3600), 2, 1024 * 1024),
+            &chain_context,
+        );
+        assert!(chain_context.append(&create_transaction(0, 100)).is_ok());
+        assert!(chain_context.append(&create_transaction(100, 200)).is_ok());
+        assert!(wait_for_block(&chain_context, 1));
+        assert_eq!(
+            chain_context
+                .get_block(1)
+                .unwrap()
+                .unwrap()
+                .get_transactions()
+                .len(),
+            2
+        );
+        scheduler.shutdown();
+    }
+
+    #[test]
+    fn test_pool_bytes() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let scheduler = Scheduler::start(
+            SchedulerOption::new(Duration::from_secs(3600), 1000, 1),
+            &chain_context,
+        );
+        assert!(chain_context.append(&create_transaction(0, 100)).is_ok());
+        assert!(wait_for_block(&chain_context, 1));
+        scheduler.shutdown();
+    }
+
+    #[test]
+    fn test_trigger_and_shutdown() {
+        let chain_context: ChainContext = Default::default();
+        assert!(chain_context.deposit(&create_deposit(0, 200)).is_ok());
+        let scheduler = Scheduler::start(
+            SchedulerOption::new(Duration::from_secs(3600), 1000, 1024 * 1024),
+            &chain_context,
+        );
+        scheduler.trigger();
+        assert!(wait_for_block(&chain_context, 1));
+        scheduler.shutdown();
+        assert!(chain_context.append(&create_transaction(0, 100)).is_ok());
+        assert_eq!(chain_context.get_block_number().unwrap(), 1);
+    }
+}
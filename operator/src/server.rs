@@ -6,12 +6,21 @@
 //
 
 /// json rpc server.
+use super::context::ChainContext;
 use super::error::Error;
-use super::rpc::plasmarpc::PlasmaRpc;
+use super::rpc::adminrpc::AdminRpc;
+use super::rpc::adminrpcimpl::AdminRpcImpl;
+use super::rpc::auth::TokenAuth;
 use super::rpc::plasmarpcimpl::PlasmaRpcImpl;
+use super::rpc::pubsub::PlasmaPubSub;
+use super::rpc::pubsubimpl::PlasmaPubSubImpl;
 use jsonrpc_http_server::jsonrpc_core::IoHandler;
 use jsonrpc_http_server::{Server, ServerBuilder};
+use jsonrpc_pubsub::{PubSubHandler, Session};
+use jsonrpc_ws_server::{RequestContext, Server as WsServer, ServerBuilder as WsServerBuilder};
+use plasma_rpc::plasmarpc::PlasmaRpc;
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 /// Options for Plasma JSON RPC server.
 pub struct HttpOption {
@@ -28,16 +37,101 @@ impl Default for HttpOption {
     }
 }
 
-pub fn get_server(options: &HttpOption) -> Result<Server, Error> {
+/// Options for Plasma JSON RPC server over WebSocket.
+pub struct WsOption {
+    url: String,
+}
+
+impl Default for WsOption {
+    fn default() -> Self {
+        Self {
+            url: "127.0.0.1:8081".to_string(),
+        }
+    }
+}
+
+/// Options for the admin JSON RPC server.
+/// It should listen on an address which isn't exposed to public.
+pub struct AdminOption {
+    threads: usize,
+    url: String,
+    token: String,
+}
+
+impl AdminOption {
+    pub fn new(url: &str, token: &str) -> Self {
+        Self {
+            threads: 1,
+            url: url.to_string(),
+            token: token.to_string(),
+        }
+    }
+}
+
+impl HttpOption {
+    pub fn new(url: &str, threads: usize) -> Self {
+        Self {
+            threads,
+            url: url.to_string(),
+        }
+    }
+}
+
+impl WsOption {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+pub fn get_server(options: &HttpOption, chain_context: &ChainContext) -> Result<Server, Error> {
+    let mut io = IoHandler::new();
+
+    let rpc = PlasmaRpcImpl::from_chain_context(chain_context.clone());
+    io.extend_with(rpc.to_delegate());
+
+    let parsed: Result<SocketAddr, Error> = options.url.parse().map_err(Into::into);
+    let url = parsed?;
+    ServerBuilder::new(io)
+        .threads(options.threads)
+        .start_http(&url)
+        .map_err(Into::into)
+}
+
+/// WebSocket server serves the same methods as HTTP, and subscriptions
+pub fn get_ws_server(options: &WsOption, chain_context: &ChainContext) -> Result<WsServer, Error> {
+    let mut io = PubSubHandler::default();
+
+    let rpc = PlasmaRpcImpl::from_chain_context(chain_context.clone());
+    io.extend_with(rpc.to_delegate());
+    let pubsub = PlasmaPubSubImpl::new(chain_context);
+    io.extend_with(pubsub.to_delegate());
+
+    let parsed: Result<SocketAddr, Error> = options.url.parse().map_err(Into::into);
+    let url = parsed?;
+    WsServerBuilder::with_meta_extractor(io, |context: &RequestContext| {
+        Arc::new(Session::new(context.sender()))
+    })
+    .start(&url)
+    .map_err(Into::into)
+}
+
+/// admin server requires `Authorization: Bearer <token>` header
+pub fn get_admin_server(
+    options: &AdminOption,
+    chain_context: &ChainContext,
+) -> Result<Server, Error> {
     let mut io = IoHandler::new();
 
-    let rpc: PlasmaRpcImpl = Default::default();
+    let rpc = AdminRpcImpl::from_chain_context(chain_context.clone());
     io.extend_with(rpc.to_delegate());
 
     let parsed: Result<SocketAddr, Error> = options.url.parse().map_err(Into::into);
     let url = parsed?;
     ServerBuilder::new(io)
         .threads(options.threads)
+        .request_middleware(TokenAuth::new(&options.token))
         .start_http(&url)
         .map_err(Into::into)
 }
@@ -0,0 +1,56 @@
+//
+// Created on Thu Jun 13 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use super::error::Error;
+use plasma_core::data_structure::StateUpdate;
+use plasma_db::impls::rangestore::memory::RangeDbMemoryImpl;
+use plasma_db::traits::db::DatabaseTrait;
+use plasma_db::traits::rangestore::RangeStore;
+
+/// StateDb holds the latest state updates of the chain.
+pub struct StateDb {
+    db: Box<dyn RangeStore + Send + Sync>,
+}
+
+impl Default for StateDb {
+    fn default() -> Self {
+        Self::new(Box::new(RangeDbMemoryImpl::open("state")))
+    }
+}
+
+impl StateDb {
+    pub fn new(db: Box<dyn RangeStore + Send + Sync>) -> Self {
+        StateDb { db }
+    }
+    /// get the latest state updates between start and end
+    pub fn get_state_updates(&self, start: u64, end: u64) -> Result<Vec<StateUpdate>, Error> {
+        let ranges = self.db.get(start, end)?;
+        ranges
+            .iter()
+            .map(|range| {
+                // a range may be a part of the stored state update
+                let state_update = StateUpdate::from_abi(range.get_value())?;
+                Ok(StateUpdate::new(
+                    state_update.get_state_object(),
+                    range.get_start(),
+                    range.get_end(),
+                    state_update.get_block_number(),
+                    *state_update.get_plasma_contract(),
+                ))
+            })
+            .collect()
+    }
+    pub fn put_state_update(&self, state_update: &StateUpdate) -> Result<(), Error> {
+        self.db
+            .put(
+                state_update.get_start(),
+                state_update.get_end(),
+                &state_update.to_abi(),
+            )
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,54 @@
+//
+// Created on Sat Jun 29 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use ethabi::Token;
+use ethereum_types::{Address, H256};
+use plasma_core::data_structure::signature::{SecretKey, Signature};
+use plasma_core::data_structure::{StateObject, StateUpdate, Transaction, Witness};
+
+/// the key of the owner of state updates made by fixtures
+pub fn owner_key() -> SecretKey {
+    SecretKey::parse(&[3u8; 32]).unwrap()
+}
+
+/// a transaction over `[start, end)` for the plasma contract at the zero address, signed by the owner
+pub fn create_transaction(start: u64, end: u64) -> Transaction {
+    create_transaction_signed_by(&owner_key(), start, end)
+}
+
+/// a transaction over `[start, end)` signed by `secret_key`
+pub fn create_transaction_signed_by(secret_key: &SecretKey, start: u64, end: u64) -> Transaction {
+    Transaction::new(
+        Address::zero(),
+        start,
+        end,
+        Transaction::create_method_id(&b"send(address)"[..]),
+        &b"parameters"[..],
+        &Witness::new(H256::zero(), H256::zero(), 0),
+    )
+    .sign(secret_key)
+    .unwrap()
+}
+
+/// a state update over `[start, end)` made in `block_number`, which belongs to the owner
+pub fn create_state_update(start: u64, end: u64, block_number: u64) -> StateUpdate {
+    StateUpdate::new(
+        &StateObject::new(
+            Address::zero(),
+            &ethabi::encode(&[Token::Address(Signature::address_of(&owner_key()))]),
+        ),
+        start,
+        end,
+        block_number,
+        Address::zero(),
+    )
+}
+
+/// a state update over `[start, end)` deposited before the first block
+pub fn create_deposit(start: u64, end: u64) -> StateUpdate {
+    create_state_update(start, end, 0)
+}
@@ -0,0 +1,390 @@
+//
+// Created on Wed Jun 26 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use super::error::{Error, ErrorKind};
+use plasma_core::data_structure::{Block, InclusionProof};
+use plasma_db::impls::kvs::kvdb::CoreDb;
+use plasma_db::traits::db::DatabaseTrait;
+use plasma_db::traits::kvs::{Batch, KeyValueStore};
+use rlp::{Rlp, RlpStream};
+use sum_merkle_tree::{get_sibling_positions, SumMerkleNode, SumMerkleTree};
+
+/// how long trees of past blocks are kept
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pruning {
+    /// keep every tree
+    Archive,
+    /// keep trees of the latest blocks
+    KeepLatest(u64),
+}
+
+/// TreeDb persists the sum merkle tree of each block node by node,
+/// so that an inclusion proof is read with a node per level.
+pub struct TreeDb {
+    db: Box<dyn KeyValueStore + Send + Sync>,
+    pruning: Pruning,
+}
+
+impl Default for TreeDb {
+    fn default() -> Self {
+        Self::new(Box::new(CoreDb::open("tree")), Pruning::Archive)
+    }
+}
+
+impl TreeDb {
+    const COUNT_PREFIX: &'static [u8] = b"count";
+    const NODE_PREFIX: &'static [u8] = b"node";
+    const PRUNED_KEY: &'static [u8] = b"pruned";
+
+    pub fn new(db: Box<dyn KeyValueStore + Send + Sync>, pruning: Pruning) -> Self {
+        TreeDb { db, pruning }
+    }
+    pub fn get_pruning(&self) -> Pruning {
+        self.pruning
+    }
+    fn count_key(block_number: u64) -> Vec<u8> {
+        let mut key = Self::COUNT_PREFIX.to_vec();
+        key.extend_from_slice(&block_number.to_be_bytes());
+        key
+    }
+    fn node_key(block_number: u64, level: usize, position: usize) -> Vec<u8> {
+        let mut key = Self::NODE_PREFIX.to_vec();
+        key.extend_from_slice(&block_number.to_be_bytes());
+        key.push(level as u8);
+        key.extend_from_slice(&(position as u64).to_be_bytes());
+        key
+    }
+    /// number of leaves of a stored tree
+    fn get_count(&self, block_number: u64) -> Result<Option<usize>, Error> {
+        Ok(self.db.get(&Self::count_key(block_number))?.map(|value| {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&value);
+            u64::from_be_bytes(bytes) as usize
+        }))
+    }
+    /// whether the tree of a block is stored
+    pub fn has_tree(&self, block_number: u64) -> Result<bool, Error> {
+        Ok(self.get_count(block_number)?.is_some())
+    }
+    /// whether the tree of a block should be kept when `latest_block_number` is the latest
+    pub fn is_retained(&self, block_number: u64, latest_block_number: u64) -> bool {
+        match self.pruning {
+            Pruning::Archive => true,
+            Pruning::KeepLatest(count) => block_number + count > latest_block_number,
+        }
+    }
+    /// store every node of the tree of a block, and prune trees which are no longer retained.
+    /// An empty block has no tree.
+    pub fn put_tree(&mut self, block: &Block) -> Result<(), Error> {
+        let tree = Block::compute_tree(block.get_transactions());
+        self.put_tree_of(block, tree.as_ref())
+    }
+    /// same as `put_tree` with the tree which is already built for the block.
+    /// Nodes, the count and pruned trees are written in a batch.
+    pub fn put_tree_of(
+        &mut self,
+        block: &Block,
+        tree: Option<&SumMerkleTree>,
+    ) -> Result<(), Error> {
+        let block_number = block.get_number();
+        let mut puts = vec![];
+        if let Some(tree) = tree {
+            for level in 0..=tree.get_depth() {
+                let nodes = tree.get_level(level).expect("level is in the tree");
+                for (position, node) in nodes.iter().enumerate() {
+                    puts.push((
+                        Self::node_key(block_number, level, position),
+                        encode_node(node),
+                    ));
+                }
+            }
+            puts.push((
+                Self::count_key(block_number),
+                (block.get_transactions().len() as u64)
+                    .to_be_bytes()
+                    .to_vec(),
+            ));
+        }
+        let dels = self.get_pruned_keys(block_number, &mut puts)?;
+        self.write(&puts, &dels)
+    }
+    /// delete every tree which is older than the retention window of `latest_block_number`
+    pub fn prune(&self, latest_block_number: u64) -> Result<(), Error> {
+        let mut puts = vec![];
+        let dels = self.get_pruned_keys(latest_block_number, &mut puts)?;
+        self.write(&puts, &dels)
+    }
+    /// keys of trees to prune since the last pruning.
+    /// The block number to prune from next time is pushed to `puts`.
+    fn get_pruned_keys(
+        &self,
+        latest_block_number: u64,
+        puts: &mut Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<Vec<Vec<u8>>, Error> {
+        let count = match self.pruning {
+            Pruning::KeepLatest(count) if count <= latest_block_number => count,
+            _ => return Ok(vec![]),
+        };
+        let pruned_from = match self.db.get(Self::PRUNED_KEY)? {
+            Some(value) => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&value);
+                u64::from_be_bytes(bytes)
+            }
+            None => 0,
+        };
+        let pruned_until = latest_block_number - count;
+        if pruned_until < pruned_from {
+            return Ok(vec![]);
+        }
+        let mut keys = vec![];
+        for block_number in pruned_from..=pruned_until {
+            keys.extend(self.get_tree_keys(block_number)?);
+        }
+        puts.push((
+            Self::PRUNED_KEY.to_vec(),
+            (pruned_until + 1).to_be_bytes().to_vec(),
+        ));
+        Ok(keys)
+    }
+    /// the count and node keys of a stored tree
+    fn get_tree_keys(&self, block_number: u64) -> Result<Vec<Vec<u8>>, Error> {
+        let count = match self.get_count(block_number)? {
+            Some(count) => count,
+            None => return Ok(vec![]),
+        };
+        let mut keys = vec![Self::count_key(block_number)];
+        let mut len = count;
+        let mut level = 0;
+        loop {
+            for position in 0..len {
+                keys.push(Self::node_key(block_number, level, position));
+            }
+            if len <= 1 {
+                return Ok(keys);
+            }
+            len = (len + 1) / 2;
+            level += 1;
+        }
+    }
+    /// delete every node of the tree of a block
+    pub fn delete_tree(&self, block_number: u64) -> Result<(), Error> {
+        self.write(&[], &self.get_tree_keys(block_number)?)
+    }
+    fn write(&self, puts: &[(Vec<u8>, Vec<u8>)], dels: &[Vec<u8>]) -> Result<(), Error> {
+        let operations: Vec<Batch> = puts
+            .iter()
+            .map(|(key, value)| Batch::BatchPut { key, value })
+            .chain(dels.iter().map(|key| Batch::BatchDel { key }))
+            .collect();
+        if operations.is_empty() {
+            return Ok(());
+        }
+        self.db.batch(&operations)?;
+        Ok(())
+    }
+    /// read an inclusion proof of a transaction at `index` from stored nodes.
+    /// Returns `None` if the tree is not stored or `index` is out of the block.
+    pub fn get_inclusion_proof(
+        &self,
+        block_number: u64,
+        index: usize,
+    ) -> Result<Option<InclusionProof>, Error> {
+        let count = match self.get_count(block_number)? {
+            Some(count) if index < count => count,
+            _ => return Ok(None),
+        };
+        let nodes: Result<Vec<SumMerkleNode>, Error> = get_sibling_positions(index, count)
+            .iter()
+            .enumerate()
+            .map(|(level, position)| match position {
+                Some(position) => self.get_node(block_number, level, *position),
+                None => Ok(SumMerkleNode::create_proof_node(
+                    &SumMerkleNode::create_empty(),
+                )),
+            })
+            .collect();
+        Ok(Some(InclusionProof::new(index as u64, &nodes?)))
+    }
+    fn get_node(
+        &self,
+        block_number: u64,
+        level: usize,
+        position: usize,
+    ) -> Result<SumMerkleNode, Error> {
+        let encoded = self
+            .db
+            .get(&Self::node_key(block_number, level, position))?
+            .ok_or_else(|| Error::from(ErrorKind::MissingTreeNode { block_number }))?;
+        let rlp = Rlp::new(&encoded);
+        let end: u64 = rlp.val_at(0)?;
+        let hash: Vec<u8> = rlp.val_at(1)?;
+        Ok(SumMerkleNode::ProofNode {
+            end,
+            data: hash.into(),
+        })
+    }
+}
+
+fn encode_node(node: &SumMerkleNode) -> Vec<u8> {
+    match node {
+        SumMerkleNode::ProofNode { end, data } => {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(end);
+            stream.append(&data.as_ref());
+            stream.out()
+        }
+        _ => unreachable!("levels of a tree consist of proof nodes"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pruning, TreeDb};
+    use crate::test_utils::create_transaction;
+    use ethereum_types::H256;
+    use plasma_core::data_structure::signature::SecretKey;
+    use plasma_core::data_structure::{Block, Transaction};
+    use plasma_core::process::BlockGenerator;
+    use plasma_db::error::Error;
+    use plasma_db::impls::kvs::kvdb::CoreDb;
+    use plasma_db::impls::kvs::leveldb::KvsLevelImpl;
+    use plasma_db::traits::db::DatabaseTrait;
+    use plasma_db::traits::kvs::{Batch, KeyValueStore, KvsIterator};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    /// key value store which counts reads
+    struct CountingDb {
+        db: CoreDb,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl KeyValueStore for CountingDb {
+        fn get(&self, key: &[u8]) -> Result<Option<Box<[u8]>>, Error> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.db.get(key)
+        }
+        fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+            self.db.put(key, value)
+        }
+        fn del(&self, key: &[u8]) -> Result<(), Error> {
+            self.db.del(key)
+        }
+        fn has(&self, key: &[u8]) -> Result<bool, Error> {
+            self.db.has(key)
+        }
+        fn batch(&self, operations: &[Batch]) -> Result<(), Error> {
+            self.db.batch(operations)
+        }
+        fn iterator(&self, prefix: &[u8]) -> Result<Box<KvsIterator>, Error> {
+            self.db.iterator(prefix)
+        }
+    }
+
+    fn create_block(number: u64, count: u64) -> Block {
+        let transactions: Vec<Transaction> = (0..count)
+            .map(|i| create_transaction(i * 100, i * 100 + 50))
+            .collect();
+        BlockGenerator::generate(
+            &transactions,
+            number,
+            &H256::zero(),
+            0,
+            &SecretKey::parse(&[1u8; 32]).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_inclusion_proof() {
+        let reads = Arc::new(AtomicUsize::new(0));
+        let mut db = TreeDb::new(
+            Box::new(CountingDb {
+                db: CoreDb::open("tree"),
+                reads: reads.clone(),
+            }),
+            Pruning::Archive,
+        );
+        let block = create_block(1, 37);
+        assert!(db.put_tree(&block).is_ok());
+        for index in 0..37 {
+            reads.store(0, Ordering::SeqCst);
+            let inclusion_proof = db.get_inclusion_proof(1, index).unwrap().unwrap();
+            assert_eq!(
+                Some(inclusion_proof.clone()),
+                block.get_inclusion_proof(index)
+            );
+            // the count and a node per level
+            assert!(reads.load(Ordering::SeqCst) <= inclusion_proof.get_nodes().len() + 1);
+            assert!(inclusion_proof.verify(
+                &Block::create_leaf(&block.get_transactions()[index]),
+                block.get_root()
+            ));
+        }
+        assert_eq!(db.get_inclusion_proof(1, 37).unwrap(), None);
+        assert_eq!(db.get_inclusion_proof(2, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_empty_block() {
+        let mut db: TreeDb = Default::default();
+        assert!(db.put_tree(&create_block(1, 0)).is_ok());
+        assert!(!db.has_tree(1).unwrap());
+        assert_eq!(db.get_inclusion_proof(1, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_pruning() {
+        let mut db = TreeDb::new(Box::new(CoreDb::open("tree")), Pruning::KeepLatest(2));
+        for number in 1..=4 {
+            assert!(db.put_tree(&create_block(number, 3)).is_ok());
+        }
+        assert!(!db.has_tree(1).unwrap());
+        assert!(!db.has_tree(2).unwrap());
+        assert!(db.has_tree(3).unwrap());
+        assert!(db.has_tree(4).unwrap());
+        assert!(!db.is_retained(2, 4));
+        assert!(db.is_retained(3, 4));
+        assert_eq!(db.get_inclusion_proof(2, 0).unwrap(), None);
+        assert!(db.get_inclusion_proof(3, 0).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_prune_older_trees() {
+        let dir = TempDir::new("tree").unwrap();
+        let open = |pruning| {
+            TreeDb::new(
+                Box::new(KvsLevelImpl::open_path(dir.path()).unwrap()),
+                pruning,
+            )
+        };
+        {
+            let mut db = open(Pruning::Archive);
+            for number in 1..=4 {
+                let transactions = vec![create_transaction(0, 50)];
+                let (block, tree) = BlockGenerator::generate_with_tree(
+                    &transactions,
+                    number,
+                    &H256::zero(),
+                    0,
+                    &SecretKey::parse(&[1u8; 32]).unwrap(),
+                )
+                .unwrap();
+                assert!(db.put_tree_of(&block, tree.as_ref()).is_ok());
+            }
+        }
+        // the retention window is shortened after restart
+        let db = open(Pruning::KeepLatest(1));
+        assert!(db.prune(4).is_ok());
+        for number in 1..=3 {
+            assert!(!db.has_tree(number).unwrap());
+        }
+        assert!(db.has_tree(4).unwrap());
+    }
+}
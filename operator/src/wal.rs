@@ -0,0 +1,191 @@
+//
+// Created on Fri Jun 14 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use super::error::Error;
+use plasma_core::data_structure::Transaction;
+use plasma_db::impls::kvs::kvdb::CoreDb;
+use plasma_db::traits::db::DatabaseTrait;
+use plasma_db::traits::kvs::{Batch, KeyValueStore};
+use std::slice;
+
+/// Write-ahead log of pending transactions.
+/// Entries between `tail` and `head` are transactions which aren't included in a persisted block yet.
+pub struct TransactionLog {
+    db: Box<dyn KeyValueStore + Send + Sync>,
+    head: u64,
+    tail: u64,
+}
+
+impl Default for TransactionLog {
+    fn default() -> Self {
+        Self::new(Box::new(CoreDb::open("wal"))).expect("empty log can't fail")
+    }
+}
+
+fn decode_u64(bytes: Option<Box<[u8]>>) -> u64 {
+    bytes.map_or(0, |bytes| {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&bytes);
+        u64::from_be_bytes(buf)
+    })
+}
+
+impl TransactionLog {
+    const HEAD_KEY: &'static [u8] = b"head";
+    const TAIL_KEY: &'static [u8] = b"tail";
+    const ENTRY_PREFIX: &'static [u8] = b"entry";
+
+    /// open a log, which may have entries written before restart
+    pub fn new(db: Box<dyn KeyValueStore + Send + Sync>) -> Result<Self, Error> {
+        let head = decode_u64(db.get(Self::HEAD_KEY)?);
+        let tail = decode_u64(db.get(Self::TAIL_KEY)?);
+        Ok(TransactionLog { db, head, tail })
+    }
+    fn entry_key(sequence: u64) -> Vec<u8> {
+        let mut key = Self::ENTRY_PREFIX.to_vec();
+        key.extend_from_slice(&sequence.to_be_bytes());
+        key
+    }
+    pub fn append(&mut self, transaction: &Transaction) -> Result<(), Error> {
+        self.append_batch(slice::from_ref(transaction))
+    }
+    /// the entries and the moved head are written at once,
+    /// so a crash never leaves a part of the transactions in the log
+    pub fn append_batch(&mut self, transactions: &[Transaction]) -> Result<(), Error> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = transactions
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| {
+                (
+                    Self::entry_key(self.head + index as u64),
+                    transaction.to_abi(),
+                )
+            })
+            .collect();
+        let head = self.head + transactions.len() as u64;
+        let head_bytes = head.to_be_bytes();
+        let mut operations: Vec<Batch> = entries
+            .iter()
+            .map(|(key, value)| Batch::BatchPut { key, value })
+            .collect();
+        operations.push(Batch::BatchPut {
+            key: Self::HEAD_KEY,
+            value: &head_bytes,
+        });
+        self.db.batch(&operations)?;
+        self.head = head;
+        Ok(())
+    }
+    /// transactions in the order they were appended
+    pub fn get_transactions(&self) -> Result<Vec<Transaction>, Error> {
+        (self.tail..self.head)
+            .filter_map(|sequence| match self.db.get(&Self::entry_key(sequence)) {
+                Ok(Some(encoded)) => Some(Transaction::from_abi(&encoded).map_err(Into::into)),
+                Ok(None) => None,
+                Err(err) => Some(Err(err.into())),
+            })
+            .collect()
+    }
+    /// discard all entries, called after they are included in a persisted block.
+    /// The moved tail and the deleted entries are written at once, as `append_batch` does.
+    pub fn truncate(&mut self) -> Result<(), Error> {
+        let keys: Vec<Vec<u8>> = (self.tail..self.head).map(Self::entry_key).collect();
+        let tail_bytes = self.head.to_be_bytes();
+        let mut operations: Vec<Batch> = keys.iter().map(|key| Batch::BatchDel { key }).collect();
+        operations.push(Batch::BatchPut {
+            key: Self::TAIL_KEY,
+            value: &tail_bytes,
+        });
+        self.db.batch(&operations)?;
+        self.tail = self.head;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionLog;
+    use crate::test_utils::create_transaction;
+    use plasma_db::impls::kvs::leveldb::KvsLevelImpl;
+    use tempdir::TempDir;
+
+    fn open(dir: &TempDir) -> TransactionLog {
+        TransactionLog::new(Box::new(KvsLevelImpl::open_path(dir.path()).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn test_replay_after_restart() {
+        let dir = TempDir::new("wal").unwrap();
+        {
+            let mut log = open(&dir);
+            assert!(log.append(&create_transaction(0, 100)).is_ok());
+            assert!(log.truncate().is_ok());
+            assert!(log.append(&create_transaction(100, 200)).is_ok());
+            assert!(log.append(&create_transaction(200, 300)).is_ok());
+        }
+        let log = open(&dir);
+        assert_eq!(
+            log.get_transactions().unwrap(),
+            vec![create_transaction(100, 200), create_transaction(200, 300)]
+        );
+    }
+
+    #[test]
+    fn test_truncate_after_restart() {
+        let dir = TempDir::new("wal").unwrap();
+        {
+            let mut log = open(&dir);
+            assert!(log
+                .append_batch(&[create_transaction(0, 100), create_transaction(100, 200)])
+                .is_ok());
+            assert!(log.truncate().is_ok());
+        }
+        let mut log = open(&dir);
+        assert_eq!(log.get_transactions().unwrap(), vec![]);
+        assert!(log.append(&create_transaction(200, 300)).is_ok());
+        assert_eq!(
+            log.get_transactions().unwrap(),
+            vec![create_transaction(200, 300)]
+        );
+    }
+
+    #[test]
+    fn test_append_batch() {
+        let dir = TempDir::new("wal").unwrap();
+        let transactions = vec![create_transaction(0, 100), create_transaction(100, 200)];
+        {
+            let mut log = open(&dir);
+            assert!(log.append_batch(&transactions).is_ok());
+            assert!(log.append(&create_transaction(200, 300)).is_ok());
+        }
+        let log = open(&dir);
+        assert_eq!(
+            log.get_transactions().unwrap(),
+            vec![
+                create_transaction(0, 100),
+                create_transaction(100, 200),
+                create_transaction(200, 300)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut log: TransactionLog = Default::default();
+        assert!(log.append(&create_transaction(0, 100)).is_ok());
+        assert!(log.truncate().is_ok());
+        assert_eq!(log.get_transactions().unwrap(), vec![]);
+        assert!(log.append(&create_transaction(100, 200)).is_ok());
+        assert_eq!(
+            log.get_transactions().unwrap(),
+            vec![create_transaction(100, 200)]
+        );
+    }
+}
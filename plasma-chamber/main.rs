@@ -1,7 +1,178 @@
+extern crate clap;
+extern crate plasma_client;
 extern crate plasma_operator;
 
-use plasma_operator::entry;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use plasma_operator::config::Config;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+fn config_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("config")
+            .long("config")
+            .short("c")
+            .value_name("FILE")
+            .help("TOML configuration file"),
+        Arg::with_name("log-level")
+            .long("log-level")
+            .value_name("FILTER")
+            .help("Overrides log_level"),
+        Arg::with_name("address")
+            .long("address")
+            .value_name("ADDRESS")
+            .help("Overrides address of JSON RPC over HTTP"),
+        Arg::with_name("threads")
+            .long("threads")
+            .value_name("N")
+            .help("Overrides the number of HTTP server threads"),
+        Arg::with_name("ws-address")
+            .long("ws-address")
+            .value_name("ADDRESS")
+            .help("Overrides address of JSON RPC over WebSocket"),
+        Arg::with_name("admin-address")
+            .long("admin-address")
+            .value_name("ADDRESS")
+            .help("Overrides address of admin JSON RPC"),
+        Arg::with_name("db-path")
+            .long("db-path")
+            .value_name("DIR")
+            .help("Overrides db_path"),
+        Arg::with_name("plasma-contract")
+            .long("plasma-contract")
+            .value_name("ADDRESS")
+            .help("Overrides plasma_contract"),
+        Arg::with_name("l1-endpoint")
+            .long("l1-endpoint")
+            .value_name("URL")
+            .help("Overrides l1_endpoint"),
+        Arg::with_name("operator-key")
+            .long("operator-key")
+            .value_name("FILE")
+            .help("Overrides operator_key_path"),
+        Arg::with_name("block-interval")
+            .long("block-interval")
+            .value_name("SECONDS")
+            .help("Overrides block_interval"),
+        Arg::with_name("block-max-transactions")
+            .long("block-max-transactions")
+            .value_name("N")
+            .help("Overrides block_max_transactions"),
+        Arg::with_name("block-max-bytes")
+            .long("block-max-bytes")
+            .value_name("BYTES")
+            .help("Overrides block_max_bytes"),
+        Arg::with_name("tree-retention")
+            .long("tree-retention")
+            .value_name("BLOCKS")
+            .help("Overrides tree_retention"),
+    ]
+}
+
+fn parse_number<T: std::str::FromStr>(
+    matches: &ArgMatches,
+    name: &str,
+) -> Result<Option<T>, String> {
+    matches
+        .value_of(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("--{} should be a number", name))
+        })
+        .transpose()
+}
+
+/// load a configuration file, and override it with flags
+fn load_config(matches: &ArgMatches) -> Result<Config, String> {
+    let mut config = match matches.value_of("config") {
+        Some(path) => Config::load(Path::new(path)).map_err(|err| err.to_string())?,
+        None => Config::default(),
+    };
+    let strings: Vec<(&str, &mut String)> = vec![
+        ("log-level", &mut config.log_level),
+        ("address", &mut config.address),
+        ("ws-address", &mut config.ws_address),
+        ("admin-address", &mut config.admin_address),
+        ("db-path", &mut config.db_path),
+        ("plasma-contract", &mut config.plasma_contract),
+        ("l1-endpoint", &mut config.l1_endpoint),
+        ("operator-key", &mut config.operator_key_path),
+    ];
+    for (name, field) in strings {
+        if let Some(value) = matches.value_of(name) {
+            *field = value.to_string();
+        }
+    }
+    if let Some(threads) = parse_number(matches, "threads")? {
+        config.threads = threads;
+    }
+    if let Some(block_interval) = parse_number(matches, "block-interval")? {
+        config.block_interval = block_interval;
+    }
+    if let Some(block_max_transactions) = parse_number(matches, "block-max-transactions")? {
+        config.block_max_transactions = block_max_transactions;
+    }
+    if let Some(block_max_bytes) = parse_number(matches, "block-max-bytes")? {
+        config.block_max_bytes = block_max_bytes;
+    }
+    if let Some(tree_retention) = parse_number(matches, "tree-retention")? {
+        config.tree_retention = tree_retention;
+    }
+    config.validate().map_err(|err| err.to_string())?;
+    Ok(config)
+}
+
+fn inspect(config: &Config) -> Result<(), String> {
+    print!("{}", config.to_toml().map_err(|err| err.to_string())?);
+    if !config.operator_key_path.is_empty() {
+        let operator = config
+            .get_operator_address()
+            .map_err(|err| err.to_string())?;
+        println!("# operator address: {:?}", operator);
+    }
+    Ok(())
+}
 
 fn main() {
-    entry();
+    let matches = App::new("plasma-chamber")
+        .about("Plasma Chamber operator and client")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("operator")
+                .about("Runs the operator")
+                .args(&config_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("watchtower")
+                .about("Runs the client watchtower against the in-memory plasma contract")
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("SECONDS")
+                        .default_value("10")
+                        .help("Interval of polling exits"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect")
+                .about("Validates the operator configuration and prints it")
+                .args(&config_args()),
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("operator", Some(matches)) => load_config(matches)
+            .and_then(|config| plasma_operator::entry(&config).map_err(|err| err.to_string())),
+        ("watchtower", Some(matches)) => parse_number(matches, "interval").map(|interval| {
+            plasma_client::watchtower::entry(Duration::from_secs(interval.unwrap_or(10)))
+        }),
+        ("inspect", Some(matches)) => load_config(matches).and_then(|config| inspect(&config)),
+        _ => Ok(()),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
 }
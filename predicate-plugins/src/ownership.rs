@@ -1,7 +1,12 @@
 use crate::predicate::PredicatePlugin;
+use ethabi::ParamType;
+use ethereum_types::Address;
+use plasma_core::data_structure::error::{Error, ErrorKind};
 use plasma_core::data_structure::{StateUpdate, Transaction};
 
-/// Simple ownership predicate
+/// Simple ownership predicate.
+/// The data of its state object is the ABI encoded owner address,
+/// and a transaction has to be signed by the owner.
 pub struct OwnershipPredicate {}
 
 impl Default for OwnershipPredicate {
@@ -10,14 +15,109 @@ impl Default for OwnershipPredicate {
     }
 }
 
+impl OwnershipPredicate {
+    /// the owner of a state update, which is decoded from the data of its state object
+    pub fn get_owner(state_update: &StateUpdate) -> Result<Address, Error> {
+        ethabi::decode(
+            &[ParamType::Address],
+            state_update.get_state_object().get_data(),
+        )
+        .ok()
+        .and_then(|decoded| decoded[0].clone().to_address())
+        .ok_or_else(|| Error::from(ErrorKind::AbiDecode))
+    }
+}
+
 impl PredicatePlugin for OwnershipPredicate {
     fn execute_state_transition(
         &self,
         input: &StateUpdate,
-        _transaction: &Transaction,
-    ) -> StateUpdate {
+        transaction: &Transaction,
+    ) -> Result<StateUpdate, Error> {
+        let owner = Self::get_owner(input)?;
+        let signer = transaction
+            .get_witness()
+            .recover(&transaction.get_body_hash())?;
+        if signer != owner {
+            return Err(Error::from(ErrorKind::InvalidSignature));
+        }
         // should parse transaction.parameters
         // make new state update
-        input.clone()
+        Ok(input.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OwnershipPredicate;
+    use crate::predicate::PredicatePlugin;
+    use ethabi::Token;
+    use ethereum_types::{Address, H256};
+    use plasma_core::data_structure::error::ErrorKind;
+    use plasma_core::data_structure::signature::{SecretKey, Signature};
+    use plasma_core::data_structure::{StateObject, StateUpdate, Transaction, Witness};
+
+    fn create_state_update(owner: Address) -> StateUpdate {
+        StateUpdate::new(
+            &StateObject::new(Address::zero(), &ethabi::encode(&[Token::Address(owner)])),
+            0,
+            100,
+            0,
+            Address::zero(),
+        )
+    }
+
+    fn create_transaction(secret_key: &SecretKey) -> Transaction {
+        Transaction::new(
+            Address::zero(),
+            0,
+            100,
+            Transaction::create_method_id(&b"send(address)"[..]),
+            &b"parameters"[..],
+            &Witness::new(H256::zero(), H256::zero(), 0),
+        )
+        .sign(secret_key)
+        .unwrap()
+    }
+
+    #[test]
+    fn test_execute_state_transition() {
+        let owner_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let input = create_state_update(Signature::address_of(&owner_key));
+        let predicate: OwnershipPredicate = Default::default();
+        assert_eq!(
+            predicate
+                .execute_state_transition(&input, &create_transaction(&owner_key))
+                .unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn test_reject_transaction_of_non_owner() {
+        let owner_key = SecretKey::parse(&[1u8; 32]).unwrap();
+        let other_key = SecretKey::parse(&[2u8; 32]).unwrap();
+        let input = create_state_update(Signature::address_of(&owner_key));
+        let predicate: OwnershipPredicate = Default::default();
+        match predicate
+            .execute_state_transition(&input, &create_transaction(&other_key))
+            .unwrap_err()
+            .kind()
+        {
+            ErrorKind::InvalidSignature => {}
+            _ => panic!("a transaction of non owner should be rejected"),
+        }
+        // a transaction without signature
+        let unsigned = Transaction::new(
+            Address::zero(),
+            0,
+            100,
+            Transaction::create_method_id(&b"send(address)"[..]),
+            &b"parameters"[..],
+            &Witness::new(H256::zero(), H256::zero(), 0),
+        );
+        assert!(predicate
+            .execute_state_transition(&input, &unsigned)
+            .is_err());
     }
 }
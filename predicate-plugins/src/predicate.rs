@@ -1,10 +1,12 @@
+use plasma_core::data_structure::error::Error;
 use plasma_core::data_structure::{StateUpdate, Transaction};
 
 /// Base class of predicate plugin
 pub trait PredicatePlugin {
+    /// Returns a new state update, or an error if the transition is invalid
     fn execute_state_transition(
         &self,
         input: &StateUpdate,
         transaction: &Transaction,
-    ) -> StateUpdate;
+    ) -> Result<StateUpdate, Error>;
 }
@@ -0,0 +1,223 @@
+//
+// Created on Mon Jun 24 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+use crate::plasmarpc::{gen_client, TransactionResult, TransactionStatus};
+use ethereum_types::H256;
+use failure::format_err;
+use jsonrpc_core::futures::sync::mpsc;
+use jsonrpc_core::futures::{Future, Sink, Stream};
+use jsonrpc_core::{Error as JsonRpcError, MetaIoHandler, Metadata};
+use jsonrpc_core_client::transports::{duplex, local};
+use jsonrpc_core_client::RpcError;
+use jsonrpc_http_server::hyper::header::{HeaderValue, CONTENT_TYPE};
+use jsonrpc_http_server::hyper::{Body, Client, Request, Uri};
+use plasma_core::data_structure::{
+    Block, InclusionProof, InclusionReceipt, StateUpdate, Transaction,
+};
+use std::fmt::Display;
+use std::ops::Deref;
+
+fn parse_error<E>(target: &str) -> impl FnOnce(E) -> RpcError
+where
+    E: Display,
+{
+    let target = target.to_string();
+    move |err| RpcError::ParseError(target, format_err!("{}", err))
+}
+
+fn decode_hex(target: &str, message: &str) -> Result<Vec<u8>, RpcError> {
+    hex::decode(message.trim_start_matches("0x")).map_err(parse_error(target))
+}
+
+fn decode_receipt(message: &str) -> Result<InclusionReceipt, RpcError> {
+    InclusionReceipt::from_abi(&decode_hex("InclusionReceipt", message)?)
+        .map_err(parse_error("InclusionReceipt"))
+}
+
+fn decode_state_update(message: &str) -> Result<StateUpdate, RpcError> {
+    StateUpdate::from_abi(&decode_hex("StateUpdate", message)?).map_err(parse_error("StateUpdate"))
+}
+
+/// Typed client of the Plasma JSON RPC.
+/// Values are decoded from their ABI or RLP encoding.
+#[derive(Clone)]
+pub struct OperatorClient {
+    inner: gen_client::Client,
+}
+
+impl OperatorClient {
+    pub fn new(inner: gen_client::Client) -> Self {
+        OperatorClient { inner }
+    }
+
+    /// Connects to an operator over HTTP.
+    /// The returned future sends requests and has to be spawned on a runtime.
+    pub fn connect_http(
+        url: &str,
+    ) -> Result<(Self, impl Future<Item = (), Error = RpcError>), RpcError> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|err| RpcError::Other(format_err!("{}", err)))?;
+        let http_client = Client::new();
+        let (request_sender, request_receiver) = mpsc::unbounded::<String>();
+        let (response_sender, response_receiver) = mpsc::unbounded::<String>();
+        let requests = request_receiver
+            .map_err(|()| RpcError::Other(format_err!("request channel is closed")))
+            .for_each(move |body| {
+                let mut request = Request::post(uri.clone())
+                    .body(Body::from(body))
+                    .expect("uri is already parsed");
+                request
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                let response_sender = response_sender.clone();
+                http_client
+                    .request(request)
+                    .and_then(|response| response.into_body().concat2())
+                    .map_err(|err| RpcError::Other(err.into()))
+                    .and_then(move |body| {
+                        let response =
+                            String::from_utf8(body.to_vec()).map_err(parse_error("String"))?;
+                        response_sender
+                            .unbounded_send(response)
+                            .map_err(|err| RpcError::Other(err.into()))
+                    })
+            });
+        let (client, channel) = duplex::duplex(
+            request_sender.sink_map_err(|err| RpcError::Other(err.into())),
+            response_receiver
+                .map_err(|()| RpcError::Other(format_err!("response channel is closed"))),
+        );
+        Ok((
+            OperatorClient::new(channel.into()),
+            client.join(requests).map(|_| ()),
+        ))
+    }
+
+    /// Connects to an in-process handler, mainly for tests.
+    pub fn connect_local<THandler, TMetadata>(
+        handler: THandler,
+    ) -> (Self, impl Future<Item = (), Error = RpcError>)
+    where
+        THandler: Deref<Target = MetaIoHandler<TMetadata>>,
+        TMetadata: Metadata + Default,
+    {
+        let (client, future) = local::connect::<gen_client::Client, _, _>(handler);
+        (OperatorClient::new(client), future)
+    }
+
+    pub fn protocol_version(&self) -> impl Future<Item = String, Error = RpcError> {
+        self.inner.protocol_version()
+    }
+
+    pub fn send_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> impl Future<Item = InclusionReceipt, Error = RpcError> {
+        self.inner
+            .send_transaction(hex::encode(transaction.to_abi()))
+            .and_then(|receipt| decode_receipt(&receipt))
+    }
+
+    /// Sends transactions in a batch, and returns a result for each of them.
+    pub fn send_transactions(
+        &self,
+        transactions: &[Transaction],
+        atomic: bool,
+    ) -> impl Future<Item = Vec<Result<InclusionReceipt, JsonRpcError>>, Error = RpcError> {
+        let messages = transactions
+            .iter()
+            .map(|transaction| hex::encode(transaction.to_abi()))
+            .collect();
+        self.inner
+            .send_transactions(messages, Some(atomic))
+            .and_then(|results| {
+                results
+                    .into_iter()
+                    .map(|result| match result {
+                        TransactionResult::Receipt(receipt) => decode_receipt(&receipt).map(Ok),
+                        TransactionResult::Error(err) => Ok(Err(err)),
+                    })
+                    .collect()
+            })
+    }
+
+    pub fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> impl Future<Item = Vec<StateUpdate>, Error = RpcError> {
+        self.inner
+            .simulate_transaction(hex::encode(transaction.to_abi()))
+            .and_then(|state_updates| {
+                state_updates
+                    .iter()
+                    .map(|state_update| decode_state_update(state_update.get_abi()))
+                    .collect()
+            })
+    }
+
+    pub fn get_block_number(&self) -> impl Future<Item = u64, Error = RpcError> {
+        self.inner.get_block_number()
+    }
+
+    pub fn get_block(&self, number: u64) -> impl Future<Item = Block, Error = RpcError> {
+        self.inner.get_block(number).and_then(|block| {
+            rlp::decode(&decode_hex("Block", &block)?).map_err(parse_error("Block"))
+        })
+    }
+
+    pub fn get_transaction(
+        &self,
+        hash: &H256,
+    ) -> impl Future<Item = Transaction, Error = RpcError> {
+        self.inner
+            .get_transaction(format!("0x{}", hex::encode(hash.as_bytes())))
+            .and_then(|transaction| {
+                Transaction::from_abi(&decode_hex("Transaction", &transaction)?)
+                    .map_err(parse_error("Transaction"))
+            })
+    }
+
+    pub fn get_state_updates(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Future<Item = Vec<StateUpdate>, Error = RpcError> {
+        self.inner
+            .get_state_updates(start, end)
+            .and_then(|state_updates| {
+                state_updates
+                    .iter()
+                    .map(|state_update| decode_state_update(state_update))
+                    .collect()
+            })
+    }
+
+    pub fn get_transaction_status(
+        &self,
+        hash: &H256,
+    ) -> impl Future<Item = TransactionStatus, Error = RpcError> {
+        self.inner
+            .get_transaction_status(format!("0x{}", hex::encode(hash.as_bytes())))
+    }
+
+    /// Returns an inclusion proof of a transaction with the number of the block including it.
+    pub fn get_inclusion_proof(
+        &self,
+        hash: &H256,
+    ) -> impl Future<Item = (u64, InclusionProof), Error = RpcError> {
+        self.inner
+            .get_inclusion_proof(format!("0x{}", hex::encode(hash.as_bytes())))
+            .and_then(|result| {
+                result
+                    .get_inclusion_proof()
+                    .to_inclusion_proof()
+                    .map(|inclusion_proof| (result.get_block_number(), inclusion_proof))
+                    .map_err(parse_error("InclusionProof"))
+            })
+    }
+}
@@ -0,0 +1,14 @@
+//
+// Created on Sat Jun 29 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+extern crate serde;
+extern crate serde_derive;
+
+/// typed client of the operator JSON RPC.
+pub mod client;
+/// Plasma JSON RPC trait and the types on the wire.
+pub mod plasmarpc;
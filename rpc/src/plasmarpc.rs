@@ -0,0 +1,140 @@
+//
+// Created on Wed May 08 2019
+//
+// Copyright (c) 2019 Cryptoeconomics Lab, Inc.
+// This file is part of Plasma Chamber.
+//
+
+extern crate jsonrpc_core;
+extern crate jsonrpc_derive;
+extern crate plasma_core;
+
+use jsonrpc_core::{Error as JsonRpcError, Result};
+use jsonrpc_derive::rpc;
+use plasma_core::data_structure::inclusion_proof::InclusionProofJson;
+use plasma_core::data_structure::{InclusionProof, StateUpdate};
+use serde_derive::{Deserialize, Serialize};
+
+/// status of a transaction known by the operator
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum TransactionStatus {
+    Pending,
+    Included {
+        #[serde(rename = "blockNumber")]
+        block_number: u64,
+    },
+    Unknown,
+}
+
+/// result of a transaction in a batch
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionResult {
+    /// inclusion receipt encoded by ABI
+    Receipt(String),
+    Error(JsonRpcError),
+}
+
+/// state update encoded by ABI, and its decoded fields
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateUpdateJson {
+    abi: String,
+    start: u64,
+    end: u64,
+    block_number: u64,
+    plasma_contract: String,
+    predicate: String,
+    data: String,
+}
+
+impl StateUpdateJson {
+    pub fn new(state_update: &StateUpdate) -> Self {
+        StateUpdateJson {
+            abi: hex::encode(state_update.to_abi()),
+            start: state_update.get_start(),
+            end: state_update.get_end(),
+            block_number: state_update.get_block_number(),
+            plasma_contract: format!(
+                "0x{}",
+                hex::encode(state_update.get_plasma_contract().as_bytes())
+            ),
+            predicate: format!(
+                "0x{}",
+                hex::encode(state_update.get_state_object().get_predicate().as_bytes())
+            ),
+            data: format!(
+                "0x{}",
+                hex::encode(state_update.get_state_object().get_data())
+            ),
+        }
+    }
+    pub fn get_abi(&self) -> &str {
+        &self.abi
+    }
+}
+
+/// inclusion proof of a transaction, and the number of the block including it
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProofResult {
+    block_number: u64,
+    inclusion_proof: InclusionProofJson,
+}
+
+impl InclusionProofResult {
+    pub fn new(block_number: u64, inclusion_proof: &InclusionProof) -> Self {
+        InclusionProofResult {
+            block_number,
+            inclusion_proof: InclusionProofJson::new(inclusion_proof),
+        }
+    }
+    pub fn get_block_number(&self) -> u64 {
+        self.block_number
+    }
+    pub fn get_inclusion_proof(&self) -> &InclusionProofJson {
+        &self.inclusion_proof
+    }
+}
+
+/// Plasma JSON RPC trait.
+#[rpc]
+pub trait PlasmaRpc {
+    /// Returns a protocol version
+    #[rpc(name = "protocolVersion")]
+    fn protocol_version(&self) -> Result<String>;
+    /// append signed transaction, and returns an operator-signed inclusion receipt
+    #[rpc(name = "sendTransaction")]
+    fn send_transaction(&self, message: String) -> Result<String>;
+    /// append a batch of signed transactions, and returns a result for each of them.
+    /// Unless `atomic` is false, no transaction is appended if any of them is invalid.
+    /// The operator rejects a batch of more transactions than its limit.
+    #[rpc(name = "sendTransactions")]
+    fn send_transactions(
+        &self,
+        messages: Vec<String>,
+        atomic: Option<bool>,
+    ) -> Result<Vec<TransactionResult>>;
+    /// Returns state updates which a transaction would make, without appending it
+    #[rpc(name = "simulateTransaction")]
+    fn simulate_transaction(&self, message: String) -> Result<Vec<StateUpdateJson>>;
+    /// Returns the number of the latest block
+    #[rpc(name = "getBlockNumber")]
+    fn get_block_number(&self) -> Result<u64>;
+    /// Returns a block encoded by RLP
+    #[rpc(name = "getBlock")]
+    fn get_block(&self, number: u64) -> Result<String>;
+    /// Returns a transaction encoded by ABI
+    #[rpc(name = "getTransaction")]
+    fn get_transaction(&self, hash: String) -> Result<String>;
+    /// Returns the latest state updates in a range encoded by ABI
+    #[rpc(name = "getStateUpdates")]
+    fn get_state_updates(&self, start: u64, end: u64) -> Result<Vec<String>>;
+    /// Returns a status of a transaction
+    #[rpc(name = "getTransactionStatus")]
+    fn get_transaction_status(&self, hash: String) -> Result<TransactionStatus>;
+    /// Returns an inclusion proof of a transaction with the number of the block including it
+    #[rpc(name = "getInclusionProof")]
+    fn get_inclusion_proof(&self, hash: String) -> Result<InclusionProofResult>;
+}
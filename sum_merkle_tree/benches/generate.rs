@@ -0,0 +1,41 @@
+#[macro_use]
+extern crate criterion;
+
+use bytes::Bytes;
+use criterion::Criterion;
+use sum_merkle_tree::{Keccak256, SumMerkleNode, SumMerkleTree};
+
+fn create_leaves(count: u64) -> Vec<SumMerkleNode> {
+    (0..count)
+        .map(|i| SumMerkleNode::create_leaf((i + 1) * 100, Bytes::from(&i.to_be_bytes()[..])))
+        .collect()
+}
+
+fn bench_generate(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "generate",
+        |b, &&count| {
+            let leaves = create_leaves(count);
+            b.iter(|| SumMerkleTree::<Keccak256>::generate(&leaves))
+        },
+        &[1_000, 10_000, 100_000, 500_000],
+    );
+}
+
+fn bench_inclusion_proof(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "get_inclusion_proof",
+        |b, &&count| {
+            let tree: SumMerkleTree = SumMerkleTree::generate(&create_leaves(count));
+            b.iter(|| tree.get_inclusion_proof(count as usize / 2, count as usize))
+        },
+        &[1_000, 100_000],
+    );
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_generate, bench_inclusion_proof
+}
+criterion_main!(benches);
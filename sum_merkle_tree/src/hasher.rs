@@ -0,0 +1,101 @@
+use crypto::blake2b::Blake2b;
+use crypto::digest::Digest;
+use crypto::sha2::Sha256 as Sha256Digest;
+use crypto::sha3::Sha3;
+
+/// Hasher is a hash function of the tree, whose output is 32 bytes.
+pub trait Hasher: Sized {
+    fn new() -> Self;
+    fn update(&mut self, data: &[u8]);
+    fn finish(self) -> [u8; 32];
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finish()
+    }
+}
+
+fn finish_digest<D: Digest>(mut digest: D) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    digest.result(&mut result);
+    result
+}
+
+/// Keccak-256, which is the default hash function and the one of the plasma contract
+pub struct Keccak256(Sha3);
+
+impl Hasher for Keccak256 {
+    fn new() -> Self {
+        Keccak256(Sha3::keccak256())
+    }
+    fn update(&mut self, data: &[u8]) {
+        self.0.input(data)
+    }
+    fn finish(self) -> [u8; 32] {
+        finish_digest(self.0)
+    }
+}
+
+/// SHA-256, for chains with a cheaper SHA-256 precompile
+pub struct Sha256(Sha256Digest);
+
+impl Hasher for Sha256 {
+    fn new() -> Self {
+        Sha256(Sha256Digest::new())
+    }
+    fn update(&mut self, data: &[u8]) {
+        self.0.input(data)
+    }
+    fn finish(self) -> [u8; 32] {
+        finish_digest(self.0)
+    }
+}
+
+/// BLAKE2b with 32 bytes output
+pub struct Blake2b256(Blake2b);
+
+impl Hasher for Blake2b256 {
+    fn new() -> Self {
+        Blake2b256(Blake2b::new(32))
+    }
+    fn update(&mut self, data: &[u8]) {
+        self.0.input(data)
+    }
+    fn finish(self) -> [u8; 32] {
+        finish_digest(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blake2b256, Hasher, Keccak256, Sha256};
+
+    #[test]
+    fn test_empty_input() {
+        assert_eq!(
+            hex(&Keccak256::hash(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex(&Sha256::hash(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&Blake2b256::hash(b"")),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"mess");
+        hasher.update(b"age");
+        assert_eq!(hasher.finish(), Sha256::hash(b"message"));
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
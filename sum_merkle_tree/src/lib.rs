@@ -1,26 +1,28 @@
 extern crate crypto;
 
-use self::crypto::sha3::Sha3;
-use byteorder::{LittleEndian, WriteBytesExt};
+pub mod hasher;
+
+pub use self::hasher::{Blake2b256, Hasher, Keccak256, Sha256};
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
 use bytes::Bytes;
-use crypto::digest::Digest;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
 
 #[derive(Debug)]
 pub enum Error {
     VerifyError,
 }
 
-fn hash_leaf(value: &Bytes) -> Bytes {
-    let mut hasher = Sha3::keccak256();
-    let mut result = vec![0u8; hasher.output_bits() / 8];
-    hasher.reset();
-    hasher.input(value.as_ref());
-    hasher.result(result.as_mut_slice());
-    Bytes::from(result)
+fn hash_leaf<H: Hasher>(value: &[u8]) -> Bytes {
+    Bytes::from(&H::hash(value)[..])
 }
 
 trait Hashable {
-    fn hash(&self) -> Bytes;
+    fn hash<H: Hasher>(&self) -> Bytes;
 }
 
 /// SumMerkleNode is a node in merkle tree
@@ -62,25 +64,25 @@ pub enum SumMerkleNode {
     },
 }
 
-/// Caluculate hash of a node
-fn compute_node(end: u64, data: &Bytes) -> Bytes {
+/// Caluculate hash of a node, whose end is a big-endian u64 as `abi.encodePacked(uint64)`
+fn compute_node<H: Hasher>(end: u64, data: &Bytes) -> Bytes {
     let mut end_writer = vec![];
-    end_writer.write_u64::<LittleEndian>(end).unwrap();
+    end_writer.write_u64::<BigEndian>(end).unwrap();
     let mut buf = Bytes::new();
     buf.extend_from_slice(&end_writer);
     buf.extend_from_slice(&data);
-    hash_leaf(&buf)
+    hash_leaf::<H>(&buf)
 }
 
 impl Hashable for SumMerkleNode {
-    fn hash(&self) -> Bytes {
+    fn hash<H: Hasher>(&self) -> Bytes {
         match self {
-            SumMerkleNode::Leaf { data, .. } => hash_leaf(data),
+            SumMerkleNode::Leaf { data, .. } => hash_leaf::<H>(data),
             // H(H(left.end + left.data) + H(right.end + right.data))
             SumMerkleNode::Node { left, right, .. } => {
-                let mut buf = compute_node(left.get_end(), &left.hash());
-                buf.extend_from_slice(&compute_node(right.get_end(), &right.hash()));
-                hash_leaf(&buf)
+                let mut buf = compute_node::<H>(left.get_end(), &left.hash::<H>());
+                buf.extend_from_slice(&compute_node::<H>(right.get_end(), &right.hash::<H>()));
+                hash_leaf::<H>(&buf)
             }
             SumMerkleNode::ProofNode { data, .. } => data.clone(),
         }
@@ -89,16 +91,24 @@ impl Hashable for SumMerkleNode {
 
 impl SumMerkleNode {
     pub fn create_proof_node(node: &SumMerkleNode) -> SumMerkleNode {
+        SumMerkleNode::create_proof_node_with_hasher::<Keccak256>(node)
+    }
+
+    pub fn create_proof_node_with_hasher<H: Hasher>(node: &SumMerkleNode) -> SumMerkleNode {
         SumMerkleNode::ProofNode {
             end: node.get_end(),
-            data: node.hash(),
+            data: node.hash::<H>(),
         }
     }
 
     pub fn create_empty() -> Self {
+        SumMerkleNode::create_empty_with_hasher::<Keccak256>()
+    }
+
+    pub fn create_empty_with_hasher<H: Hasher>() -> Self {
         SumMerkleNode::Leaf {
             end: u64::max_value(),
-            data: hash_leaf(&Bytes::from_static(&[0u8])),
+            data: hash_leaf::<H>(&[0u8]),
         }
     }
 
@@ -140,71 +150,235 @@ impl ImplicitBounds {
             implicit_end,
         }
     }
+    pub fn get_implicit_start(&self) -> u64 {
+        self.implicit_start
+    }
+    pub fn get_implicit_end(&self) -> u64 {
+        self.implicit_end
+    }
 }
 
-#[derive(Debug)]
-pub struct SumMerkleTree {
-    tree: SumMerkleNode,
+/// MultiProof proves many leaves of a tree at once.
+/// `nodes` are siblings which can't be computed from the proven leaves,
+/// ordered from the bottom level to the top and from left to right in each level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiProof {
+    depth: usize,
+    indices: Vec<usize>,
+    nodes: Vec<SumMerkleNode>,
 }
 
-impl SumMerkleTree {
-    /// generate sum merkle tree
-    pub fn generate(leaves: &[SumMerkleNode]) -> SumMerkleTree {
-        if leaves.len() <= 1 {
-            return SumMerkleTree {
-                tree: leaves[0].clone(),
-            };
+impl MultiProof {
+    pub fn new(depth: usize, indices: &[usize], nodes: &[SumMerkleNode]) -> Self {
+        MultiProof {
+            depth,
+            indices: indices.to_vec(),
+            nodes: nodes.to_vec(),
         }
-        let mut parents = vec![];
-        for chunk in leaves.chunks(2) {
-            let v = chunk.to_vec();
-            if chunk.len() == 1 {
-                parents.push(SumMerkleNode::compute_parent(
-                    &v[0],
-                    &SumMerkleNode::create_empty(),
-                ))
-            } else {
-                parents.push(SumMerkleNode::compute_parent(&v[0].clone(), &v[1].clone()))
-            }
+    }
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+    /// indices of proven leaves in ascending order
+    pub fn get_indices(&self) -> &[usize] {
+        &self.indices
+    }
+    pub fn get_nodes(&self) -> &[SumMerkleNode] {
+        &self.nodes
+    }
+}
+
+/// end and hash of a node, which is all the tree keeps for each node
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct HashedNode {
+    end: u64,
+    hash: [u8; 32],
+}
+
+impl HashedNode {
+    fn from_node<H: Hasher>(node: &SumMerkleNode) -> Self {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&node.hash::<H>());
+        HashedNode {
+            end: node.get_end(),
+            hash,
+        }
+    }
+
+    /// H(H(left.end + left.hash) + H(right.end + right.hash)), without allocating buffers
+    fn compute_parent<H: Hasher>(left: &HashedNode, right: &HashedNode) -> Self {
+        let mut hasher = H::new();
+        hasher.update(&left.hash_with_end::<H>());
+        hasher.update(&right.hash_with_end::<H>());
+        HashedNode {
+            end: right.end,
+            hash: hasher.finish(),
+        }
+    }
+
+    fn hash_with_end<H: Hasher>(&self) -> [u8; 32] {
+        let mut end_bytes = [0u8; 8];
+        BigEndian::write_u64(&mut end_bytes, self.end);
+        let mut hasher = H::new();
+        hasher.update(&end_bytes);
+        hasher.update(&self.hash);
+        hasher.finish()
+    }
+
+    fn to_proof_node(&self) -> SumMerkleNode {
+        SumMerkleNode::ProofNode {
+            end: self.end,
+            data: Bytes::from(&self.hash[..]),
+        }
+    }
+}
+
+/// hash leaves, in parallel if the `parallel` feature is enabled
+#[cfg(feature = "parallel")]
+fn hash_leaves<H: Hasher>(leaves: &[SumMerkleNode]) -> Vec<HashedNode> {
+    leaves.par_iter().map(HashedNode::from_node::<H>).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn hash_leaves<H: Hasher>(leaves: &[SumMerkleNode]) -> Vec<HashedNode> {
+    leaves.iter().map(HashedNode::from_node::<H>).collect()
+}
+
+/// compute parents of a level, in parallel if the `parallel` feature is enabled
+#[cfg(feature = "parallel")]
+fn compute_parents<H: Hasher>(nodes: &[HashedNode], empty: &HashedNode) -> Vec<HashedNode> {
+    nodes
+        .par_chunks(2)
+        .map(|chunk| HashedNode::compute_parent::<H>(&chunk[0], chunk.get(1).unwrap_or(empty)))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn compute_parents<H: Hasher>(nodes: &[HashedNode], empty: &HashedNode) -> Vec<HashedNode> {
+    nodes
+        .chunks(2)
+        .map(|chunk| HashedNode::compute_parent::<H>(&chunk[0], chunk.get(1).unwrap_or(empty)))
+        .collect()
+}
+
+/// Returns positions of siblings on the path of a leaf at `idx` from the bottom level to the top,
+/// in a tree of `count` leaves. `None` is an empty leaf paired with the last node of a level.
+/// A proof can be read with them from nodes stored outside of the tree.
+pub fn get_sibling_positions(idx: usize, count: usize) -> Vec<Option<usize>> {
+    let mut positions = vec![];
+    let mut position = idx;
+    let mut len = count;
+    while len > 1 {
+        let sibling = position ^ 0x01;
+        positions.push(if sibling < len { Some(sibling) } else { None });
+        position >>= 1;
+        len = (len + 1) / 2;
+    }
+    positions
+}
+
+/// SumMerkleTree keeps every level of the tree from the leaves to the root.
+/// The last node of a level with odd number of nodes is paired with an empty leaf.
+/// Nodes are hashed by `H`, which is Keccak-256 by default.
+pub struct SumMerkleTree<H: Hasher = Keccak256> {
+    levels: Vec<Vec<HashedNode>>,
+    empty: HashedNode,
+    hasher: PhantomData<fn() -> H>,
+}
+
+impl<H: Hasher> fmt::Debug for SumMerkleTree<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SumMerkleTree")
+            .field("levels", &self.levels)
+            .finish()
+    }
+}
+
+impl<H: Hasher> SumMerkleTree<H> {
+    /// generate sum merkle tree level by level.
+    /// The tree of no leaves has the zero root.
+    pub fn generate(leaves: &[SumMerkleNode]) -> Self {
+        let empty = HashedNode::from_node::<H>(&SumMerkleNode::create_empty_with_hasher::<H>());
+        let mut levels = vec![hash_leaves::<H>(leaves)];
+        while levels[levels.len() - 1].len() > 1 {
+            let parents = compute_parents::<H>(&levels[levels.len() - 1], &empty);
+            levels.push(parents);
+        }
+        SumMerkleTree {
+            levels,
+            empty,
+            hasher: PhantomData,
         }
-        SumMerkleTree::generate(&parents)
     }
 
     /// Calculate merkle root
     pub fn get_root(&self) -> Bytes {
-        self.tree.hash()
+        match self.levels[self.levels.len() - 1].first() {
+            Some(root) => Bytes::from(&root.hash[..]),
+            None => Bytes::from(&[0u8; 32][..]),
+        }
+    }
+
+    /// the number of levels above leaves
+    pub fn get_depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// nodes of a level as proof nodes, counting levels from the leaves
+    pub fn get_level(&self, level: usize) -> Option<Vec<SumMerkleNode>> {
+        self.levels
+            .get(level)
+            .map(|nodes| nodes.iter().map(HashedNode::to_proof_node).collect())
+    }
+
+    /// get a node at `position` of a level, counting levels from the leaves.
+    /// The node next to the last one of a level is an empty leaf.
+    fn get_node(&self, level: usize, position: usize) -> Option<&HashedNode> {
+        let nodes = self.levels.get(level)?;
+        if position < nodes.len() {
+            Some(&nodes[position])
+        } else if position == nodes.len() && position % 2 == 1 {
+            Some(&self.empty)
+        } else {
+            None
+        }
     }
 
     /// Returns inclusion proof for a leaf
     pub fn get_inclusion_proof(&self, idx: usize, count: usize) -> Vec<SumMerkleNode> {
-        SumMerkleTree::get_inclusion_proof_of_tree(&self.tree, idx, count)
+        if idx >= count || idx >= self.levels[0].len() {
+            return vec![];
+        }
+        get_sibling_positions(idx, self.levels[0].len())
+            .iter()
+            .enumerate()
+            .map(|(level, position)| match position {
+                Some(position) => self.levels[level][*position].to_proof_node(),
+                None => self.empty.to_proof_node(),
+            })
+            .collect()
     }
 
-    fn get_inclusion_proof_of_tree(
-        tree: &SumMerkleNode,
-        idx: usize,
-        count: usize,
-    ) -> Vec<SumMerkleNode> {
-        match tree {
-            SumMerkleNode::Leaf { .. } => vec![],
-            SumMerkleNode::Node { left, right, .. } => {
-                let left_count = count.next_power_of_two() / 2;
-                if idx < left_count {
-                    let mut proofs = Self::get_inclusion_proof_of_tree(left, idx, left_count);
-                    proofs.push(SumMerkleNode::create_proof_node(&right));
-                    proofs
-                } else {
-                    let mut proofs = Self::get_inclusion_proof_of_tree(
-                        right,
-                        idx - left_count,
-                        count - left_count,
-                    );
-                    proofs.push(SumMerkleNode::create_proof_node(&left));
-                    proofs
-                }
+    /// Returns exclusion proof for a range, which is the index of the leaf whose implicit bounds
+    /// include `[start, end)` and its inclusion proof.
+    /// The range is not touched in the tree unless the leaf itself touches it.
+    /// Returns `None` if the range spans more than one leaf.
+    pub fn get_exclusion_proof(&self, start: u64, end: u64) -> Option<(usize, Vec<SumMerkleNode>)> {
+        let leaves = &self.levels[0];
+        let count = leaves.len();
+        let mut implicit_start = 0;
+        for (idx, leaf) in leaves.iter().enumerate() {
+            let implicit_end = if idx == count - 1 {
+                u64::max_value()
+            } else {
+                leaf.end
+            };
+            if implicit_start <= start && end <= implicit_end {
+                return Some((idx, self.get_inclusion_proof(idx, count)));
             }
-            SumMerkleNode::ProofNode { .. } => vec![],
+            implicit_start = leaf.end;
         }
+        None
     }
 
     /// get_path
@@ -223,76 +397,238 @@ impl SumMerkleTree {
     ///     0  1  2  3
     /// ```
     ///
-    fn get_path(idx: usize, depth: usize, path: &mut Vec<bool>) {
-        if depth == 0 {
-            return;
+    /// Returns `None` if `idx` doesn't fit in a tree of `depth`, or `depth` is 64 or more.
+    fn get_path(idx: usize, depth: usize) -> Option<Vec<bool>> {
+        if depth >= 64 || (idx as u64) >> depth != 0 {
+            return None;
         }
-        path.push((idx & 0x01) != 0);
-        Self::get_path(idx.rotate_right(1), depth - 1, path)
+        Some((0..depth).map(|i| (idx >> i) & 0x01 != 0).collect())
+    }
+
+    /// Verify whether leaf is included or not, and returns its implicit bounds.
+    ///
+    /// Leaves are sorted by their ends, and a leaf covers the range from the end of
+    /// the previous leaf to its own end: `[end(i - 1), end(i))`.
+    /// The first leaf starts at `0`, and the last leaf which is followed only by padding leaves
+    /// covers until `u64::max_value()`. So implicit bounds of leaves in a tree never overlap.
+    ///
+    /// Ends of every node in the proof are checked as well as hashes, so that a proof
+    /// from a tree whose leaves are not sorted fails:
+    /// - a left child never ends after its right sibling
+    /// - a left sibling on the path never ends after the implicit start of the leaf
+    /// - the leaf never ends before its implicit start
+    pub fn verify(
+        leaf: &SumMerkleNode,
+        idx: usize,
+        inclusion_proof: Vec<SumMerkleNode>,
+        root: &Bytes,
+    ) -> Result<ImplicitBounds, Error> {
+        // indices of leaves have only 64 bits
+        if inclusion_proof.len() >= 64 {
+            return Err(Error::VerifyError);
+        }
+        let path = Self::get_path(idx, inclusion_proof.len()).ok_or(Error::VerifyError)?;
+        // the implicit start is the end of the nearest left sibling, which is the previous leaf
+        let implicit_start = path
+            .iter()
+            .position(|&is_right| is_right)
+            .map_or(0, |pos| inclusion_proof[pos].get_end());
+        if leaf.get_end() < implicit_start {
+            return Err(Error::VerifyError);
+        }
+        let mut computed = leaf.clone();
+        for (item, &is_right) in inclusion_proof.iter().zip(path.iter()) {
+            computed = if is_right {
+                // leaf is in right
+                if item.get_end() > implicit_start {
+                    return Err(Error::VerifyError);
+                }
+                Self::verify_and_get_parent(item, &computed)?
+            } else {
+                // leaf is in left
+                Self::verify_and_get_parent(&computed, item)?
+            }
+        }
+        if computed.hash::<H>() != root {
+            return Err(Error::VerifyError);
+        }
+        // the last leaf has only padding leaves on its right
+        let empty = SumMerkleNode::create_proof_node_with_hasher::<H>(
+            &SumMerkleNode::create_empty_with_hasher::<H>(),
+        );
+        let is_last_leaf = inclusion_proof
+            .iter()
+            .zip(path.iter())
+            .all(|(node, &is_right)| is_right || *node == empty);
+        Ok(ImplicitBounds::new(
+            implicit_start,
+            if is_last_leaf {
+                u64::max_value()
+            } else {
+                leaf.get_end()
+            },
+        ))
     }
 
     fn verify_and_get_parent(
         left: &SumMerkleNode,
         right: &SumMerkleNode,
-        _first_left_end: u64,
     ) -> Result<SumMerkleNode, Error> {
-        /*
-        if left.get_end() > first_left_end {
-            return Err(Error::VerifyError);
-        }
-        */
         if left.get_end() > right.get_end() {
             return Err(Error::VerifyError);
         }
-        Ok(SumMerkleNode::compute_parent(left, right))
+        // keep only the hash of the parent, so that it isn't hashed again on the next level
+        Ok(SumMerkleNode::create_proof_node_with_hasher::<H>(
+            &SumMerkleNode::compute_parent(left, right),
+        ))
     }
 
-    /// Verify whether leaf is included or not
-    pub fn verify(
+    /// Verify that `leaf` is included, and its implicit bounds include `[start, end)`
+    pub fn verify_exclusion(
         leaf: &SumMerkleNode,
         idx: usize,
         inclusion_proof: Vec<SumMerkleNode>,
         root: &Bytes,
+        start: u64,
+        end: u64,
     ) -> Result<ImplicitBounds, Error> {
-        let mut path: Vec<bool> = vec![];
-        Self::get_path(idx, inclusion_proof.len(), path.as_mut());
-        println!("{:?}, {:?}", path, inclusion_proof);
-        let first_left_end = path
+        let implicit_bounds = Self::verify(leaf, idx, inclusion_proof, root)?;
+        if implicit_bounds.implicit_start <= start && end <= implicit_bounds.implicit_end {
+            Ok(implicit_bounds)
+        } else {
+            Err(Error::VerifyError)
+        }
+    }
+
+    /// Returns a proof of leaves at `indices`, which shares sibling nodes between the leaves.
+    /// Returns `None` if any of indices is out of `count`.
+    pub fn get_multi_proof(&self, indices: &[usize], count: usize) -> Option<MultiProof> {
+        let mut indices = indices.to_vec();
+        indices.sort();
+        indices.dedup();
+        let last = *indices.last()?;
+        if last >= count || last >= self.levels[0].len() {
+            return None;
+        }
+        let depth = self.get_depth();
+        let mut nodes = vec![];
+        let mut known = indices.clone();
+        for level in 0..depth {
+            for (i, position) in known.iter().enumerate() {
+                let sibling = position ^ 0x01;
+                let is_known = (i > 0 && known[i - 1] == sibling)
+                    || (i + 1 < known.len() && known[i + 1] == sibling);
+                if !is_known {
+                    nodes.push(self.get_node(level, sibling)?.to_proof_node());
+                }
+            }
+            known = known.iter().map(|position| position >> 1).collect();
+            known.dedup();
+        }
+        Some(MultiProof::new(depth, &indices, &nodes))
+    }
+
+    /// Verify leaves with a multi proof, and returns their implicit bounds.
+    /// `leaves` are in the order of indices of the proof.
+    ///
+    /// Nodes are computed once for all leaves, and bounds of each leaf are checked
+    /// against its siblings in the same pass, as `verify` does for a single leaf.
+    pub fn verify_multi_proof(
+        leaves: &[SumMerkleNode],
+        multi_proof: &MultiProof,
+        root: &Bytes,
+    ) -> Result<Vec<ImplicitBounds>, Error> {
+        let depth = multi_proof.get_depth();
+        let indices = multi_proof.get_indices();
+        if depth >= 64
+            || leaves.is_empty()
+            || leaves.len() != indices.len()
+            || indices.windows(2).any(|pair| pair[0] >= pair[1])
+            || (indices[indices.len() - 1] as u64) >> depth != 0
+        {
+            return Err(Error::VerifyError);
+        }
+        let empty = SumMerkleNode::create_proof_node_with_hasher::<H>(
+            &SumMerkleNode::create_empty_with_hasher::<H>(),
+        );
+        // the end of the nearest left sibling of each leaf, and whether it has only padding on its right
+        let mut implicit_starts: Vec<Option<u64>> = vec![None; leaves.len()];
+        let mut is_last_leaf = vec![true; leaves.len()];
+        // nodes which are computed or given in the current level
+        let mut nodes: BTreeMap<usize, SumMerkleNode> = indices
             .iter()
-            .position(|&p| p)
-            .map(|pos| inclusion_proof[pos].clone())
-            .map_or(0, |n| n.get_end());
-        let mut computed = leaf.clone();
-        for (i, item) in inclusion_proof.iter().enumerate() {
-            if path[i] {
-                // leaf is in right
-                computed = Self::verify_and_get_parent(item, &computed, first_left_end)?
-            } else {
-                // leaf is in left
-                computed = Self::verify_and_get_parent(&computed, item, first_left_end)?
+            .cloned()
+            .zip(leaves.iter().cloned())
+            .collect();
+        let mut proof_nodes = multi_proof.get_nodes().iter();
+        for level in 0..depth {
+            let positions: Vec<usize> = nodes.keys().cloned().collect();
+            for position in positions {
+                if let Entry::Vacant(entry) = nodes.entry(position ^ 0x01) {
+                    entry.insert(proof_nodes.next().ok_or(Error::VerifyError)?.clone());
+                }
+            }
+            for (i, index) in indices.iter().enumerate() {
+                let position = index >> level;
+                let sibling = &nodes[&(position ^ 0x01)];
+                if position & 0x01 != 0 {
+                    // leaf is in right
+                    let implicit_start = *implicit_starts[i].get_or_insert(sibling.get_end());
+                    if sibling.get_end() > implicit_start {
+                        return Err(Error::VerifyError);
+                    }
+                } else if is_last_leaf[i] {
+                    is_last_leaf[i] =
+                        SumMerkleNode::create_proof_node_with_hasher::<H>(sibling) == empty;
+                }
             }
+            let mut parents = BTreeMap::new();
+            for (&position, left) in nodes.iter().filter(|(position, _)| *position & 0x01 == 0) {
+                let right = &nodes[&(position ^ 0x01)];
+                parents.insert(position >> 1, Self::verify_and_get_parent(left, right)?);
+            }
+            nodes = parents;
         }
-        let is_last_leaf = 2u64.pow(inclusion_proof.len() as u32) - 1 == (idx as u64);
-        if computed.hash() == root {
-            Ok(ImplicitBounds::new(
-                first_left_end,
-                if is_last_leaf {
-                    u64::max_value()
-                } else {
-                    leaf.get_end()
-                },
-            ))
-        } else {
-            Err(Error::VerifyError)
+        if proof_nodes.next().is_some() || nodes[&0].hash::<H>() != root {
+            return Err(Error::VerifyError);
         }
+        leaves
+            .iter()
+            .zip(implicit_starts.iter().zip(is_last_leaf.iter()))
+            .map(|(leaf, (implicit_start, &is_last_leaf))| {
+                let implicit_start = implicit_start.unwrap_or(0);
+                if leaf.get_end() < implicit_start {
+                    return Err(Error::VerifyError);
+                }
+                Ok(ImplicitBounds::new(
+                    implicit_start,
+                    if is_last_leaf {
+                        u64::max_value()
+                    } else {
+                        leaf.get_end()
+                    },
+                ))
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Bytes;
+    use super::Hashable;
     use super::SumMerkleNode;
-    use super::SumMerkleTree;
+    use super::{get_sibling_positions, ImplicitBounds, MultiProof};
+    use super::{Blake2b256, Hasher, Keccak256, Sha256};
+    use proptest::prelude::*;
+
+    type SumMerkleTree = super::SumMerkleTree<Keccak256>;
+
+    fn create_leaves(ends: &[u64]) -> Vec<SumMerkleNode> {
+        ends.iter()
+            .map(|end| SumMerkleNode::create_leaf(*end, Bytes::from(&b"message"[..])))
+            .collect()
+    }
 
     #[test]
     fn test_compute_parent() {
@@ -383,4 +719,409 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_proof_of_every_leaf() {
+        for count in 1..10 {
+            let ends: Vec<u64> = (1..=count).map(|i| i * 100).collect();
+            let leaves = create_leaves(&ends);
+            let tree = SumMerkleTree::generate(&leaves);
+            for (idx, leaf) in leaves.iter().enumerate() {
+                let inclusion_proof = tree.get_inclusion_proof(idx, leaves.len());
+                assert!(
+                    SumMerkleTree::verify(leaf, idx, inclusion_proof, &tree.get_root()).is_ok(),
+                    "leaf {} of {}",
+                    idx,
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exclusion_proof() {
+        let leaves = create_leaves(&[100, 200, 300, 400, 500]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let root = tree.get_root();
+        // (start, end, index of the leaf, implicit bounds)
+        let cases = [
+            (0, 10, 0, ImplicitBounds::new(0, 100)),
+            (100, 200, 1, ImplicitBounds::new(100, 200)),
+            (350, 400, 3, ImplicitBounds::new(300, 400)),
+            (400, 500, 4, ImplicitBounds::new(400, u64::max_value())),
+            (600, 700, 4, ImplicitBounds::new(400, u64::max_value())),
+        ];
+        for (start, end, idx, implicit_bounds) in cases.iter() {
+            let (leaf_idx, inclusion_proof) = tree.get_exclusion_proof(*start, *end).unwrap();
+            assert_eq!(leaf_idx, *idx);
+            assert_eq!(
+                SumMerkleTree::verify_exclusion(
+                    &leaves[*idx],
+                    *idx,
+                    inclusion_proof,
+                    &root,
+                    *start,
+                    *end
+                )
+                .unwrap(),
+                *implicit_bounds
+            );
+        }
+        assert!(tree.get_exclusion_proof(150, 250).is_none());
+    }
+
+    #[test]
+    fn test_exclusion_proof_of_single_leaf() {
+        let leaves = create_leaves(&[100]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let (idx, inclusion_proof) = tree.get_exclusion_proof(200, 300).unwrap();
+        assert_eq!(idx, 0);
+        assert!(SumMerkleTree::verify_exclusion(
+            &leaves[0],
+            0,
+            inclusion_proof,
+            &tree.get_root(),
+            200,
+            300
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_failed_to_verify_exclusion() {
+        let leaves = create_leaves(&[100, 200, 300]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let inclusion_proof = tree.get_inclusion_proof(1, 3);
+        assert!(SumMerkleTree::verify_exclusion(
+            &leaves[1],
+            1,
+            inclusion_proof,
+            &tree.get_root(),
+            250,
+            300
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_unsorted_leaves_fail() {
+        let leaves = create_leaves(&[200, 100]);
+        let tree = SumMerkleTree::generate(&leaves);
+        for (idx, leaf) in leaves.iter().enumerate() {
+            let inclusion_proof = tree.get_inclusion_proof(idx, leaves.len());
+            assert!(SumMerkleTree::verify(leaf, idx, inclusion_proof, &tree.get_root()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_index_out_of_tree_fails() {
+        let leaves = create_leaves(&[100, 200]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let inclusion_proof = tree.get_inclusion_proof(1, leaves.len());
+        assert!(SumMerkleTree::verify(&leaves[1], 3, inclusion_proof, &tree.get_root()).is_err());
+    }
+
+    #[test]
+    fn test_too_long_proof_fails() {
+        let leaves = create_leaves(&[100, 200]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let mut inclusion_proof = tree.get_inclusion_proof(1, leaves.len());
+        let empty = SumMerkleNode::create_proof_node(&SumMerkleNode::create_empty());
+        inclusion_proof.resize(64, empty);
+        assert!(SumMerkleTree::verify(&leaves[1], 1, inclusion_proof, &tree.get_root()).is_err());
+    }
+
+    /// the recursive construction of nested nodes, to check that roots are kept
+    fn generate_root_recursively(leaves: &[SumMerkleNode]) -> Bytes {
+        if leaves.len() == 1 {
+            return leaves[0].hash::<Keccak256>();
+        }
+        let empty = SumMerkleNode::create_empty();
+        let parents: Vec<SumMerkleNode> = leaves
+            .chunks(2)
+            .map(|chunk| SumMerkleNode::compute_parent(&chunk[0], chunk.get(1).unwrap_or(&empty)))
+            .collect();
+        generate_root_recursively(&parents)
+    }
+
+    #[test]
+    fn test_root_is_kept() {
+        for count in (1..=33).chain([255, 256, 257, 1000].iter().cloned()) {
+            let ends: Vec<u64> = (1..=count).map(|i| i * 100).collect();
+            let leaves = create_leaves(&ends);
+            assert_eq!(
+                SumMerkleTree::generate(&leaves).get_root(),
+                generate_root_recursively(&leaves),
+                "{} leaves",
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_leaves() {
+        let tree = SumMerkleTree::generate(&[]);
+        assert_eq!(tree.get_root(), Bytes::from(&[0u8; 32][..]));
+        assert!(tree.get_inclusion_proof(0, 0).is_empty());
+        assert!(tree.get_exclusion_proof(0, 100).is_none());
+        assert!(tree.get_multi_proof(&[0], 1).is_none());
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_keccak_roots() {
+        // roots of leaves ending at 100, 200, ... with the default hasher, and ends as big-endian u64
+        let roots = [
+            (
+                1,
+                "c2baf6c66618acd49fb133cebc22f55bd907fe9f0d69a726d45b7539ba6bbe08",
+            ),
+            (
+                2,
+                "d0a1b411ca19301915c720f745c13fd09fd104d7cb2cd10ce53ef5fda16bac20",
+            ),
+            (
+                3,
+                "3320462fefe7391551bc966f8d7e84cc0183886376f6c8c5a0e3d69fba806e99",
+            ),
+            (
+                5,
+                "ac5386008f35e412c9e4f256e12d58212c51f675bed9cbb5f3d4662858d21919",
+            ),
+            (
+                8,
+                "735bb1f551887b61835ffe0423a6a90246e3d0130ad8e1dc12691d8cba531351",
+            ),
+        ];
+        for (count, root) in roots.iter() {
+            let ends: Vec<u64> = (1..=*count).map(|i| i * 100).collect();
+            let tree: super::SumMerkleTree = super::SumMerkleTree::generate(&create_leaves(&ends));
+            assert_eq!(to_hex(&tree.get_root()), *root);
+        }
+    }
+
+    fn assert_every_proof_verifies<H: Hasher>() {
+        let leaves = create_leaves(&[100, 200, 300, 400, 500]);
+        let tree = super::SumMerkleTree::<H>::generate(&leaves);
+        let root = tree.get_root();
+        for (idx, leaf) in leaves.iter().enumerate() {
+            let inclusion_proof = tree.get_inclusion_proof(idx, leaves.len());
+            assert!(super::SumMerkleTree::<H>::verify(leaf, idx, inclusion_proof, &root).is_ok());
+        }
+        let (idx, inclusion_proof) = tree.get_exclusion_proof(600, 700).unwrap();
+        assert!(super::SumMerkleTree::<H>::verify_exclusion(
+            &leaves[idx],
+            idx,
+            inclusion_proof,
+            &root,
+            600,
+            700
+        )
+        .is_ok());
+        let multi_proof = tree.get_multi_proof(&[1, 4], leaves.len()).unwrap();
+        assert!(super::SumMerkleTree::<H>::verify_multi_proof(
+            &[leaves[1].clone(), leaves[4].clone()],
+            &multi_proof,
+            &root
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_hashers() {
+        assert_every_proof_verifies::<Keccak256>();
+        assert_every_proof_verifies::<Sha256>();
+        assert_every_proof_verifies::<Blake2b256>();
+    }
+
+    #[test]
+    fn test_proof_of_another_hasher_fails() {
+        let leaves = create_leaves(&[100, 200, 300]);
+        let tree = super::SumMerkleTree::<Sha256>::generate(&leaves);
+        assert_ne!(tree.get_root(), SumMerkleTree::generate(&leaves).get_root());
+        assert_ne!(
+            tree.get_root(),
+            super::SumMerkleTree::<Blake2b256>::generate(&leaves).get_root()
+        );
+        let inclusion_proof = tree.get_inclusion_proof(1, leaves.len());
+        assert!(SumMerkleTree::verify(&leaves[1], 1, inclusion_proof, &tree.get_root()).is_err());
+    }
+
+    #[test]
+    fn test_sibling_positions() {
+        assert!(get_sibling_positions(0, 1).is_empty());
+        assert_eq!(get_sibling_positions(2, 5), vec![Some(3), Some(0), Some(1)]);
+        assert_eq!(get_sibling_positions(4, 5), vec![None, None, Some(0)]);
+        let leaves = create_leaves(&[100, 200, 300, 400, 500]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let levels: Vec<Vec<SumMerkleNode>> = (0..=tree.get_depth())
+            .map(|level| tree.get_level(level).unwrap())
+            .collect();
+        assert_eq!(
+            levels.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![5, 3, 2, 1]
+        );
+        let empty = SumMerkleNode::create_proof_node(&SumMerkleNode::create_empty());
+        for idx in 0..leaves.len() {
+            let inclusion_proof: Vec<SumMerkleNode> = get_sibling_positions(idx, leaves.len())
+                .iter()
+                .enumerate()
+                .map(|(level, position)| {
+                    position.map_or(empty.clone(), |p| levels[level][p].clone())
+                })
+                .collect();
+            assert_eq!(inclusion_proof, tree.get_inclusion_proof(idx, leaves.len()));
+        }
+    }
+
+    #[test]
+    fn test_proof_of_padded_level() {
+        // the last node of the second level is paired with an empty leaf
+        let leaves = create_leaves(&[100, 200, 300, 400, 500]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let inclusion_proof = tree.get_inclusion_proof(4, leaves.len());
+        let empty = SumMerkleNode::create_proof_node(&SumMerkleNode::create_empty());
+        assert_eq!(inclusion_proof.len(), 3);
+        assert_eq!(inclusion_proof[0], empty);
+        assert_eq!(inclusion_proof[1], empty);
+        assert!(SumMerkleTree::verify(&leaves[4], 4, inclusion_proof, &tree.get_root()).is_ok());
+    }
+
+    /// ends of sorted leaves
+    fn ends_strategy(min_len: usize) -> impl Strategy<Value = Vec<u64>> {
+        prop::collection::vec(1u64..1000, min_len..40).prop_map(|steps| {
+            steps
+                .iter()
+                .scan(0, |end, step| {
+                    *end += step;
+                    Some(*end)
+                })
+                .collect()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_every_honest_proof_verifies(ends in ends_strategy(1)) {
+            let leaves = create_leaves(&ends);
+            let tree = SumMerkleTree::generate(&leaves);
+            for (idx, leaf) in leaves.iter().enumerate() {
+                let inclusion_proof = tree.get_inclusion_proof(idx, leaves.len());
+                let implicit_bounds =
+                    SumMerkleTree::verify(leaf, idx, inclusion_proof, &tree.get_root()).unwrap();
+                let implicit_start = if idx == 0 { 0 } else { ends[idx - 1] };
+                let implicit_end = if idx == ends.len() - 1 {
+                    u64::max_value()
+                } else {
+                    ends[idx]
+                };
+                prop_assert_eq!(implicit_bounds, ImplicitBounds::new(implicit_start, implicit_end));
+            }
+        }
+
+        #[test]
+        fn test_every_tampered_proof_fails(
+            ends in ends_strategy(2),
+            idx_seed: usize,
+            node_seed: usize,
+            tampering in 0u8..4
+        ) {
+            let leaves = create_leaves(&ends);
+            let tree = SumMerkleTree::generate(&leaves);
+            let idx = idx_seed % leaves.len();
+            let mut inclusion_proof = tree.get_inclusion_proof(idx, leaves.len());
+            let pos = node_seed % inclusion_proof.len();
+            let mut leaf = leaves[idx].clone();
+            let mut proof_idx = idx;
+            match tampering {
+                0 => if let SumMerkleNode::ProofNode { end, .. } = &mut inclusion_proof[pos] {
+                    *end ^= 1;
+                },
+                1 => if let SumMerkleNode::ProofNode { data, .. } = &mut inclusion_proof[pos] {
+                    let mut bytes = data.to_vec();
+                    bytes[0] ^= 1;
+                    *data = Bytes::from(bytes);
+                },
+                2 => leaf = SumMerkleNode::create_leaf(ends[idx], Bytes::from(&b"tampered"[..])),
+                _ => proof_idx = (idx + 1) % leaves.len(),
+            }
+            prop_assert!(
+                SumMerkleTree::verify(&leaf, proof_idx, inclusion_proof, &tree.get_root()).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_proof() {
+        let leaves = create_leaves(&[100, 200, 300, 400, 500, 600, 700]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let root = tree.get_root();
+        for indices in [vec![0], vec![2, 3, 4], vec![0, 6], vec![1, 2, 3, 4, 5, 6]].iter() {
+            let multi_proof = tree.get_multi_proof(indices, leaves.len()).unwrap();
+            let proven: Vec<SumMerkleNode> =
+                indices.iter().map(|index| leaves[*index].clone()).collect();
+            let implicit_bounds =
+                SumMerkleTree::verify_multi_proof(&proven, &multi_proof, &root).unwrap();
+            for (index, bounds) in indices.iter().zip(implicit_bounds.iter()) {
+                let inclusion_proof = tree.get_inclusion_proof(*index, leaves.len());
+                assert_eq!(
+                    SumMerkleTree::verify(&leaves[*index], *index, inclusion_proof, &root).unwrap(),
+                    *bounds
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_proof_is_smaller_than_proofs() {
+        let leaves = create_leaves(&[100, 200, 300, 400, 500, 600, 700, 800]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let multi_proof = tree.get_multi_proof(&[2, 3, 4, 5], leaves.len()).unwrap();
+        // siblings of [2, 3] and [4, 5] at the second and the third level
+        assert_eq!(multi_proof.get_nodes().len(), 2);
+        let proofs_len: usize = [2, 3, 4, 5]
+            .iter()
+            .map(|index| tree.get_inclusion_proof(*index, leaves.len()).len())
+            .sum();
+        assert_eq!(proofs_len, 12);
+    }
+
+    #[test]
+    fn test_failed_to_verify_multi_proof() {
+        let leaves = create_leaves(&[100, 200, 300, 400, 500]);
+        let tree = SumMerkleTree::generate(&leaves);
+        let root = tree.get_root();
+        let multi_proof = tree.get_multi_proof(&[1, 4], leaves.len()).unwrap();
+        assert!(SumMerkleTree::verify_multi_proof(
+            &[leaves[1].clone(), leaves[4].clone()],
+            &multi_proof,
+            &root
+        )
+        .is_ok());
+        // leaves in a wrong order
+        assert!(SumMerkleTree::verify_multi_proof(
+            &[leaves[4].clone(), leaves[1].clone()],
+            &multi_proof,
+            &root
+        )
+        .is_err());
+        // a missing node
+        let truncated = MultiProof::new(
+            multi_proof.get_depth(),
+            multi_proof.get_indices(),
+            &multi_proof.get_nodes()[1..],
+        );
+        assert!(SumMerkleTree::verify_multi_proof(
+            &[leaves[1].clone(), leaves[4].clone()],
+            &truncated,
+            &root
+        )
+        .is_err());
+        // too deep for indices of leaves
+        let too_deep = MultiProof::new(64, &[1], &[]);
+        assert!(SumMerkleTree::verify_multi_proof(&[leaves[1].clone()], &too_deep, &root).is_err());
+        assert!(tree.get_multi_proof(&[5], leaves.len()).is_none());
+    }
 }